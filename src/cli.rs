@@ -0,0 +1,207 @@
+use std::{env, path::Path, process, str::FromStr};
+
+use crate::game::{
+    GameMode, GameSettings, GravityCurve, LockReset, PieceSet, RandomizerType, RotationSystemType,
+    ScoringSystemType, SoftDrop, FPS, MAX_GRAVITY,
+};
+
+const USAGE: &str = "Usage: tetris [OPTIONS]
+
+Options:
+  --mode <MODE>          What to play: endless, marathon, sprint or ultra
+  --goal <GOAL>          Lines to clear in marathon or sprint, or seconds to play in ultra
+  --width <CELLS>        Width of the playfield, between 4 and 64
+  --height <CELLS>       Height of the visible playfield, between 4 and 64
+  --lock-reset <RULE>    What restarts the lock delay: move, step or none. move:<N> allows N move resets instead of 15
+  --level <LEVEL>        Level to start on
+  --gravity <CURVE>      How fast pieces fall at each level: guideline, nes or 20g
+  --das <DELAY>          Delayed auto shift, in frames or milliseconds (e.g. 10 or 167ms)
+  --arr <DELAY>          Auto-repeat rate, in frames or milliseconds. 0 moves straight to the wall
+  --das-cut <DELAY>      How long rotating pauses auto-repeat, in frames or milliseconds
+  --sdf <FACTOR>         How many times faster than gravity soft drop is, or inf to drop instantly
+  --randomizer <NAME>    How the next piece is picked: 7bag, 14bag, random, nes, tgm or tgm2
+  --seed <SEED>          Play the same pieces as another game with this seed
+  --rotation <SYSTEM>    How pieces rotate and kick: srs, ars or nrs
+  --pieces <FILE>        Load piece shapes, colours and kicks from a TOML piece file instead
+  --rotate-180 <ON|OFF>  Whether the 180 rotation key is enabled
+  --all-spin <ON|OFF>    Whether pieces other than T can spin
+  --scoring <SYSTEM>     How points are awarded: guideline, nes or tgm
+  --irs <ON|OFF>         Whether holding rotate or hold as a piece spawns rotates or holds it
+  --are <DELAY>          Entry delay before each piece, in frames or milliseconds
+  --line-clear-delay <DELAY>
+                         How long cleared lines stay on the board, in frames or milliseconds
+  -h, --help             Print this message";
+
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("{message}\n\n{USAGE}");
+    process::exit(2);
+}
+
+fn parse_value<T: FromStr>(option: &str, value: &str) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| exit_with_usage(&format!("Invalid value for {option}: {value}")))
+}
+
+fn parse_lock_reset(option: &str, value: &str) -> LockReset {
+    match value.split_once(':') {
+        Some(("move", max_resets)) => LockReset::OnMove {
+            max_resets: parse_value(option, max_resets),
+        },
+        _ => match value {
+            "move" => LockReset::GUIDELINE,
+            "step" => LockReset::OnStep,
+            "none" => LockReset::Never,
+            _ => exit_with_usage(&format!("Unknown lock reset rule: {value}")),
+        },
+    }
+}
+
+fn parse_switch(option: &str, value: &str) -> bool {
+    match value {
+        "on" => true,
+        "off" => false,
+        _ => exit_with_usage(&format!("Invalid value for {option}: {value}")),
+    }
+}
+
+fn parse_soft_drop(option: &str, value: &str) -> SoftDrop {
+    if value == "inf" {
+        return SoftDrop::Instant;
+    }
+
+    let factor = parse_value(option, value);
+    if factor < 1 {
+        exit_with_usage("The soft drop factor must be at least 1");
+    }
+    SoftDrop::Factor(factor)
+}
+
+fn parse_mode(value: &str) -> GameMode {
+    match value {
+        "endless" => GameMode::Endless,
+        "marathon" => GameMode::MARATHON,
+        "sprint" => GameMode::SPRINT,
+        "ultra" => GameMode::ULTRA,
+        _ => exit_with_usage(&format!("Unknown game mode: {value}")),
+    }
+}
+
+/// Change the goal of a mode to `goal` lines, or `goal` seconds for ultra
+fn with_goal(mode: GameMode, goal: usize) -> GameMode {
+    if goal == 0 {
+        exit_with_usage("The goal must be at least 1");
+    }
+
+    match mode {
+        GameMode::Endless => exit_with_usage("Endless mode has no goal"),
+        GameMode::Marathon { .. } => GameMode::Marathon { lines: goal },
+        GameMode::Sprint { .. } => GameMode::Sprint { lines: goal },
+        GameMode::Ultra { .. } => GameMode::Ultra {
+            frames: (goal as f32 * FPS) as u64,
+        },
+    }
+}
+
+/// Parse a duration given either in frames, or in milliseconds with an `ms` suffix, as a number of frames
+fn parse_frames(option: &str, value: &str) -> u32 {
+    value.strip_suffix("ms").map_or_else(
+        || parse_value(option, value),
+        |millis| {
+            let millis: f32 = parse_value(option, millis);
+            (millis * FPS / 1000.0).round() as u32
+        },
+    )
+}
+
+/// Override any settings passed as command line options, exiting with a usage message if they can't be understood
+pub fn apply_args(settings: &mut GameSettings) {
+    let mut args = env::args().skip(1);
+    // The goal depends on the mode, which may come later
+    let mut goal: Option<usize> = None;
+
+    while let Some(option) = args.next() {
+        if option == "-h" || option == "--help" {
+            println!("{USAGE}");
+            process::exit(0);
+        }
+
+        let value = args
+            .next()
+            .unwrap_or_else(|| exit_with_usage(&format!("Missing value for {option}")));
+
+        match option.as_str() {
+            "--mode" => settings.mode = parse_mode(&value),
+            "--goal" => goal = Some(parse_value(&option, &value)),
+            "--width" => {
+                settings.board_width = parse_value(&option, &value);
+                if !(4..=64).contains(&settings.board_width) {
+                    exit_with_usage("The board width must be between 4 and 64");
+                }
+            }
+            "--height" => {
+                settings.board_height = parse_value(&option, &value);
+                if !(4..=64).contains(&settings.board_height) {
+                    exit_with_usage("The board height must be between 4 and 64");
+                }
+            }
+            "--lock-reset" => settings.lock_reset = parse_lock_reset(&option, &value),
+            "--level" => settings.start_level = parse_value(&option, &value),
+            "--gravity" => {
+                settings.gravity_curve = match value.as_str() {
+                    "guideline" => GravityCurve::Guideline,
+                    "nes" => GravityCurve::Nes,
+                    "20g" => GravityCurve::Table(vec![MAX_GRAVITY]),
+                    _ => exit_with_usage(&format!("Unknown gravity curve: {value}")),
+                }
+            }
+            "--das" => settings.das = parse_frames(&option, &value),
+            "--arr" => settings.arr = parse_frames(&option, &value),
+            "--das-cut" => settings.das_cut_delay = parse_frames(&option, &value),
+            "--sdf" => settings.soft_drop = parse_soft_drop(&option, &value),
+            "--randomizer" => {
+                settings.randomizer = match value.as_str() {
+                    "7bag" => RandomizerType::SevenBag,
+                    "14bag" => RandomizerType::FourteenBag,
+                    "random" => RandomizerType::Random,
+                    "nes" => RandomizerType::NesReroll,
+                    "tgm" => RandomizerType::TgmHistory { rolls: 4 },
+                    "tgm2" => RandomizerType::TgmHistory { rolls: 6 },
+                    _ => exit_with_usage(&format!("Unknown randomizer: {value}")),
+                }
+            }
+            "--seed" => settings.seed = Some(parse_value(&option, &value)),
+            "--rotation" => {
+                settings.rotation_system = match value.as_str() {
+                    "srs" => RotationSystemType::Srs,
+                    "ars" => RotationSystemType::Ars,
+                    "nrs" => RotationSystemType::Nrs,
+                    _ => exit_with_usage(&format!("Unknown rotation system: {value}")),
+                }
+            }
+            "--pieces" => {
+                let piece_set =
+                    PieceSet::load(Path::new(&value)).unwrap_or_else(|err| exit_with_usage(&err));
+                settings.rotation_system = RotationSystemType::Custom(piece_set);
+            }
+            "--rotate-180" => settings.allow_180_rotation = parse_switch(&option, &value),
+            "--all-spin" => settings.all_spin = parse_switch(&option, &value),
+            "--scoring" => {
+                settings.scoring = match value.as_str() {
+                    "guideline" => ScoringSystemType::Guideline,
+                    "nes" => ScoringSystemType::Nes,
+                    "tgm" => ScoringSystemType::Tgm,
+                    _ => exit_with_usage(&format!("Invalid value for {option}: {value}")),
+                }
+            }
+            "--irs" => settings.initial_actions = parse_switch(&option, &value),
+            "--are" => settings.are = parse_frames(&option, &value),
+            "--line-clear-delay" => settings.line_clear_delay = parse_frames(&option, &value),
+            _ => exit_with_usage(&format!("Unknown option: {option}")),
+        }
+    }
+
+    if let Some(goal) = goal {
+        settings.mode = with_goal(settings.mode, goal);
+    }
+}
@@ -1,221 +1,471 @@
-use std::io::stdout;
+use std::{io::stdout, mem, time::Duration};
 
-use console_input::keypress::{exit_raw_mode, Input};
 use crossterm::{
     cursor::MoveTo,
-    event::{Event, KeyCode, KeyEvent, KeyEventKind},
+    event::{Event, KeyCode, KeyEventKind},
     execute,
     terminal::{Clear, ClearType},
 };
 use gemini_engine::{
     elements::{
-        containers::CollisionContainer,
-        view::{ColChar, Modifier, Wrapping},
-        Sprite, Text, Vec2D, View,
+        view::{ColChar, Modifier, ViewElement, Wrapping},
+        Pixel, PixelContainer, Sprite, Text, Vec2D, View,
     },
     gameloop::MainLoopRoot,
 };
 
 mod alerts;
+mod auto_shift;
 mod block_manager;
-mod collision_manager;
+mod board;
+mod game_mode;
+mod game_state;
+mod gravity;
+mod key_tracker;
 mod pause;
+mod scoring;
+mod settings;
+mod terminal;
 use alerts::AlertDisplay;
-use block_manager::BlockManager;
-use collision_manager::CollisionManager;
+pub use block_manager::{PieceSet, RandomizerType, RotationSystemType};
+pub use game_mode::GameMode;
+use game_state::{Action, GameEvent, GameInput, GameOverReason, GameState};
+pub use gravity::{GravityCurve, MAX_GRAVITY};
+use key_tracker::KeyTracker;
 use pause::pause;
+pub use scoring::ScoringSystemType;
+pub use settings::{GameSettings, LockReset, SoftDrop};
+
+/// How many ticks the game runs per second. Every delay given in frames assumes this rate
+pub const FPS: f32 = 60.0;
+/// How many rows of the vanish zone to draw above the visible playfield
+const SHOWN_HIDDEN_ROWS: usize = 2;
+/// Width of the area to the right of the board used for the score, hold and next piece displays
+const HUD_WIDTH: usize = 24;
+
+/// The mode started by each number key in the menu, unless the settings have a goal for it
+const MENU_MODES: [GameMode; 4] = [
+    GameMode::MARATHON,
+    GameMode::SPRINT,
+    GameMode::ULTRA,
+    GameMode::Endless,
+];
+const GAME_OVER_OPTIONS: &str = "N for a new game
+R to retry this seed
+M for the menu
+Q to quit";
+
+/// The action performed by each key
+const fn key_binding(code: KeyCode) -> Option<Action> {
+    match code {
+        KeyCode::Left => Some(Action::ShiftLeft),
+        KeyCode::Right => Some(Action::ShiftRight),
+        KeyCode::Char('z') => Some(Action::RotateAnticlockwise),
+        KeyCode::Up | KeyCode::Char('x') => Some(Action::RotateClockwise),
+        KeyCode::Char('a') => Some(Action::Rotate180),
+        KeyCode::Down => Some(Action::SoftDrop),
+        KeyCode::Char(' ') => Some(Action::HardDrop),
+        KeyCode::Char('c') => Some(Action::Hold),
+        _ => None,
+    }
+}
+
+/// Blit an element positioned in board coordinates onto the view, with the board's top-left cell at `origin`
+fn blit_on_board(view: &mut View, origin: Vec2D, element: &impl ViewElement) {
+    let pixels: Vec<Pixel> = element
+        .active_pixels()
+        .into_iter()
+        .map(|p| Pixel::new(p.pos + origin, p.fill_char))
+        .collect();
+    view.blit_double_width(&PixelContainer::from(pixels.as_slice()), Wrapping::Ignore);
+}
 
-use self::alerts::generate_alert_for_filled_lines;
+/// Which screen is being shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    Menu,
+    Playing,
+    GameOver(GameOverReason),
+}
+
+/// Format a number of frames as minutes, seconds and hundredths
+fn format_time(frames: u64) -> String {
+    let hundredths = (frames as f32 * 100.0 / FPS) as u64;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
+
+/// Describe a mode's goal, such as "40 lines"
+fn format_goal(mode: GameMode) -> Option<String> {
+    match mode {
+        GameMode::Endless => None,
+        GameMode::Marathon { lines } | GameMode::Sprint { lines } => Some(format!("{lines} lines")),
+        GameMode::Ultra { frames } => {
+            let seconds = (frames as f32 / FPS).round() as u64;
+            Some(if seconds > 60 && seconds.is_multiple_of(60) {
+                format!("{} minutes", seconds / 60)
+            } else {
+                format!("{seconds} seconds")
+            })
+        }
+    }
+}
 
 pub struct Game {
     view: View,
     alert_display: AlertDisplay,
-    block_manager: BlockManager,
-    collision_manager: CollisionManager,
-    score: isize,
-    t: usize,
+    keys: KeyTracker,
+    state: GameState,
+    screen: Screen,
     // Constants
+    settings: GameSettings,
+    /// Position of the board's top-left cell in the view, measured in double-width cells
+    board_origin: Vec2D,
+    /// Top-left corner of the HUD in the view
+    hud_pos: Vec2D,
     controls_help_text: String,
 }
 
 impl Game {
-    pub fn new(
-        block_place_cooldown: u32,
-        piece_preview_count: usize,
-        controls_help_text: &str,
-    ) -> Self {
+    pub fn new(settings: &GameSettings, controls_help_text: &str) -> Self {
+        // The board and its walls are drawn double width
+        let hud_pos = Vec2D::new((settings.board_width as isize + 3) * 2, 0);
+        let shown_hidden_rows = settings.hidden_rows.min(SHOWN_HIDDEN_ROWS);
+        let view_height = (shown_hidden_rows + settings.board_height + 1).max(21);
+
         Self {
-            view: View::new(50, 21, ColChar::EMPTY),
-            alert_display: AlertDisplay::new(Vec2D::new(12, 7)),
-            block_manager: BlockManager::new(block_place_cooldown, piece_preview_count),
-            collision_manager: CollisionManager::new(),
-            score: 0,
-            t: 0,
+            view: View::new(hud_pos.x as usize + HUD_WIDTH, view_height, ColChar::EMPTY),
+            alert_display: AlertDisplay::new(
+                Vec2D::new(
+                    settings.board_width as isize + 2,
+                    shown_hidden_rows as isize + 7,
+                ),
+                settings.board_width * 2,
+            ),
+            keys: KeyTracker::new(terminal::enable_key_release_events()),
+            state: GameState::new(settings),
+            screen: Screen::Playing,
             // Constants
+            settings: settings.clone(),
+            board_origin: Vec2D::new(
+                1,
+                shown_hidden_rows as isize - settings.hidden_rows as isize,
+            ),
+            hud_pos,
             controls_help_text: controls_help_text.to_string(),
         }
     }
-}
-
-impl MainLoopRoot for Game {
-    type InputDataType = Event;
-
-    fn frame(&mut self, input_data: Option<Self::InputDataType>) {
-        self.t += 1;
-        let mut block_speed = 12;
-
-        // Generate a collision with the current walls and placed blocks
-        let collision = self.collision_manager.get();
-
-        // Handle Inputs
-        if let Some(Event::Key(KeyEvent {
-            code,
-            kind: KeyEventKind::Press,
-            ..
-        })) = input_data
-        {
-            match code {
-                // Pause
-                KeyCode::Esc => {
-                    self.view.clear();
-                    self.view.display_render().expect("Failed to clear screen");
-                    pause();
-                }
-
-                // Shift left
-                KeyCode::Left => {
-                    self.block_manager
-                        .try_move_block(&collision, Vec2D::new(-1, 0));
-                }
 
-                // Shift right
-                KeyCode::Right => {
-                    self.block_manager
-                        .try_move_block(&collision, Vec2D::new(1, 0));
-                }
+    /// Start a fresh game of the given mode with the given seed, or a random one if `None`
+    fn start_game(&mut self, mode: GameMode, seed: Option<u64>) {
+        self.state = GameState::new(&GameSettings {
+            mode,
+            seed,
+            ..self.settings.clone()
+        });
+        self.alert_display.clear();
+        self.keys.clear();
+        self.screen = Screen::Playing;
+    }
 
-                // Rotate anti-clockwise
-                KeyCode::Char('z') => {
-                    self.block_manager.try_rotate_block(&collision, false);
-                }
+    /// Handle the inputs for a frame of gameplay and advance the game by one tick
+    fn play_frame(&mut self, input_events: Vec<Event>) {
+        let mut input = GameInput {
+            reports_releases: self.keys.reports_releases(),
+            ..GameInput::default()
+        };
 
-                // Rotate clockwise
-                KeyCode::Up | KeyCode::Char('x') => {
-                    self.block_manager.try_rotate_block(&collision, true);
-                }
+        for event in input_events {
+            let Event::Key(key_event) = event else {
+                continue;
+            };
 
-                // Soft drop
-                KeyCode::Down => block_speed = 2,
+            if KeyTracker::is_keyboard_interrupt(&key_event) {
+                terminal::exit();
+            }
 
-                // Hard drop
-                KeyCode::Char(' ') => {
-                    self.score +=
-                        self.block_manager.ghost_block.pos.y - self.block_manager.block.pos.y;
-                    self.block_manager.block = self.block_manager.ghost_block.clone();
-                    self.t = block_speed - 1;
-                    self.block_manager.placing_cooldown = 1;
+            if self.keys.handle(&key_event) {
+                if key_event.code == KeyCode::Esc {
+                    self.view.clear();
+                    self.view.display_render().expect("Failed to clear screen");
+                    pause();
+                    // Anything queued before pausing is stale now
+                    self.keys.clear();
+                    break;
+                } else if let Some(action) = key_binding(key_event.code) {
+                    input.press(action);
                 }
-
-                KeyCode::Char('c') => self.block_manager.hold(),
-
-                _ => (),
             }
         }
+        input
+            .held
+            .extend(self.keys.held_keys().filter_map(key_binding));
+
+        for event in self.state.tick(&input) {
+            match event {
+                GameEvent::Alert { text, .. } => self.alert_display.push(&text),
+                GameEvent::GameOver(reason) => self.screen = Screen::GameOver(reason),
+                GameEvent::LevelUp(_) => self.alert_display.push("Level Up!"),
+                GameEvent::HardDrop { .. } | GameEvent::PieceLocked { .. } => (),
+            }
+        }
+    }
 
-        // Place the ghost block directly beneath the active block
-        self.block_manager.generate_ghost_block(&collision);
+    /// Handle the inputs for a frame of the menu or game over screen
+    fn menu_frame(&mut self, input_events: Vec<Event>) {
+        for event in input_events {
+            let Event::Key(key_event) = event else {
+                continue;
+            };
 
-        // If the active block is on the floor...
-        if collision.will_overlap_element(&self.block_manager.block, Vec2D::new(0, 1)) {
-            // If the block's way down is blocked...
-            self.block_manager.placing_cooldown -= 1;
-            if self.block_manager.placing_cooldown == 0 {
-                let pre_clear_blocks = self.collision_manager.stationary_blocks.clone();
+            if KeyTracker::is_keyboard_interrupt(&key_event) {
+                terminal::exit();
+            }
+            if key_event.kind != KeyEventKind::Press {
+                continue;
+            }
 
-                // If the current block is at the very top of the board...
-                if self.block_manager.reset() {
-                    println!("Game over!\r");
-                    exit_raw_mode();
+            match (self.screen, key_event.code) {
+                (Screen::Menu, KeyCode::Char(key)) if ('1'..='4').contains(&key) => {
+                    let mode = self.menu_modes()[key as usize - '1' as usize];
+                    self.start_game(mode, self.settings.seed);
                 }
-
-                let cleared_lines = self
-                    .collision_manager
-                    .blit_and_clear_lines(&self.block_manager.block);
-
-                // Display an appropriate alert
-                self.alert_display.priorised_alerts_with_score(
-                    &[
-                        self.block_manager.check_for_t_spin(
-                            &CollisionContainer::from(vec![&pre_clear_blocks as _]),
-                            cleared_lines,
-                        ),
-                        generate_alert_for_filled_lines(cleared_lines),
-                    ],
-                    &mut self.score,
-                );
-
-                self.block_manager.generate_new_block();
-            }
-        } else if self.t % block_speed == 0 {
-            // move down and increase score for soft drop
-            self.block_manager
-                .try_move_block(&collision, Vec2D::new(0, 1));
-            if block_speed == 2 {
-                self.score += 1;
+                (Screen::GameOver(_), KeyCode::Char('n')) => {
+                    self.start_game(self.state.mode(), None);
+                }
+                (Screen::GameOver(_), KeyCode::Char('r')) => {
+                    self.start_game(self.state.mode(), Some(self.state.seed));
+                }
+                (Screen::GameOver(_), KeyCode::Char('m')) => self.screen = Screen::Menu,
+                (_, KeyCode::Char('q')) => terminal::exit(),
+                _ => continue,
             }
+            break;
         }
     }
 
-    fn render_frame(&mut self) {
-        self.view.clear();
-
+    fn render_game(&mut self) {
         // Blit the walls and stationary blocks
-        self.view
-            .blit_double_width(&self.collision_manager.get(), Wrapping::Ignore);
+        blit_on_board(
+            &mut self.view,
+            self.board_origin,
+            &self.state.board.borders(),
+        );
+        blit_on_board(
+            &mut self.view,
+            self.board_origin,
+            self.state.displayed_board(),
+        );
 
-        self.view
-            .blit_double_width(&self.block_manager.ghost_block, Wrapping::Ignore);
-        self.view
-            .blit_double_width(&self.block_manager.block, Wrapping::Ignore);
+        if self.state.has_active_block() {
+            blit_on_board(
+                &mut self.view,
+                self.board_origin,
+                &self.state.block_manager.ghost_block,
+            );
+            blit_on_board(
+                &mut self.view,
+                self.board_origin,
+                &self.state.block_manager.block,
+            );
+        }
+
+        // Pieces in the HUD are drawn double width, so are positioned in half-width columns
+        let hud_piece_x = self.hud_pos.x / 2 + 2;
 
         // Next piece display
         self.view.blit(
-            &Text::new(Vec2D::new(29, 9), "Next:", Modifier::None),
+            &Text::new(self.hud_pos + Vec2D::new(3, 9), "Next:", Modifier::None),
             Wrapping::Panic,
         );
-        self.view
-            .blit_double_width(&self.block_manager.next_piece_display(), Wrapping::Ignore);
+        self.view.blit_double_width(
+            &self
+                .state
+                .block_manager
+                .next_piece_display(Vec2D::new(hud_piece_x, 12)),
+            Wrapping::Ignore,
+        );
 
         // Held piece display
-        if let Some(held_piece) = self.block_manager.held_piece_display() {
+        if let Some(held_piece) = self
+            .state
+            .block_manager
+            .held_piece_display(Vec2D::new(hud_piece_x, 4))
+        {
             self.view.blit(
-                &Text::new(Vec2D::new(29, 1), "Hold", Modifier::None),
+                &Text::new(self.hud_pos + Vec2D::new(3, 1), "Hold", Modifier::None),
                 Wrapping::Panic,
             );
             self.view.blit_double_width(&held_piece, Wrapping::Ignore);
         } else {
             self.view.blit(
-                &Sprite::new(Vec2D::new(26, 0), &self.controls_help_text, Modifier::None),
+                &Sprite::new(self.hud_pos, &self.controls_help_text, Modifier::None),
                 Wrapping::Panic,
             );
         }
 
         // Score display
+        for (i, line) in self.hud_text().iter().enumerate() {
+            self.view.blit(
+                &Text::new(
+                    self.hud_pos + Vec2D::new(0, 7 + i as isize),
+                    line,
+                    Modifier::None,
+                ),
+                Wrapping::Ignore,
+            );
+        }
+
         self.view.blit(
             &Text::new(
-                Vec2D::new(26, 7),
-                &format!("Score: {}", self.score),
+                self.hud_pos + Vec2D::new(0, self.view.height as isize - 1),
+                &format!("Seed: {}", self.state.seed),
                 Modifier::None,
             ),
-            Wrapping::Panic,
+            Wrapping::Ignore,
         );
 
         // Alerts display
         self.view.blit(&self.alert_display, Wrapping::Ignore);
         self.alert_display.frame();
+    }
+
+    /// The score and progress shown in the HUD, which depend on the mode
+    fn hud_text(&self) -> [String; 2] {
+        let state = &self.state;
+        match state.mode() {
+            GameMode::Endless => [
+                format!("Score: {}", state.score),
+                format!("Level: {}  Lines: {}", state.level, state.lines),
+            ],
+            GameMode::Marathon { lines } => [
+                format!("Score: {}", state.score),
+                format!("Level: {}  Lines: {}/{lines}", state.level, state.lines),
+            ],
+            GameMode::Sprint { lines } => [
+                format!("Time: {}", format_time(state.stats.frames)),
+                format!("Lines: {}/{lines}", state.lines),
+            ],
+            GameMode::Ultra { frames } => [
+                format!(
+                    "Time left: {}",
+                    format_time(frames.saturating_sub(state.stats.frames))
+                ),
+                format!("Score: {}  Lines: {}", state.score, state.lines),
+            ],
+        }
+    }
+
+    /// The results worth showing once a game of the current mode is over, most important first
+    fn results(&self) -> Vec<String> {
+        let state = &self.state;
+        let score = format!("Score: {}", state.score);
+        let time = format!("Time: {}", format_time(state.stats.frames));
+        let perfect_clears = format!("Perfect clears: {}", state.stats.perfect_clears);
+
+        match state.mode() {
+            GameMode::Endless | GameMode::Marathon { .. } => vec![
+                score,
+                format!("Lines: {}", state.lines),
+                format!("Level: {}", state.level),
+                time,
+                perfect_clears,
+            ],
+            GameMode::Sprint { lines } => {
+                vec![time, format!("Lines: {}/{lines}", state.lines), score]
+            }
+            GameMode::Ultra { .. } => {
+                vec![score, format!("Lines: {}", state.lines), perfect_clears]
+            }
+        }
+    }
+
+    fn render_game_over(&mut self, reason: GameOverReason) {
+        let title = match reason {
+            GameOverReason::TopOut(_) => "GAME OVER",
+            GameOverReason::GoalReached => "COMPLETE!",
+            GameOverReason::TimeUp => "TIME UP!",
+        };
+
+        let mut lines = vec![
+            String::from(title),
+            format!("{} - {reason}", self.state.mode().name()),
+            String::new(),
+        ];
+        lines.extend(self.results());
+        lines.push(format!("Seed: {}", self.state.seed));
+        lines.push(String::new());
+        lines.push(String::from(GAME_OVER_OPTIONS));
+
+        self.view.blit(
+            &Sprite::new(Vec2D::new(2, 1), &lines.join("\n"), Modifier::None),
+            Wrapping::Ignore,
+        );
+    }
+
+    /// The modes offered by the menu. The mode from the settings replaces the default one of the same kind, so a goal given on the command line carries over
+    fn menu_modes(&self) -> [GameMode; 4] {
+        MENU_MODES.map(|mode| {
+            if mem::discriminant(&mode) == mem::discriminant(&self.settings.mode) {
+                self.settings.mode
+            } else {
+                mode
+            }
+        })
+    }
+
+    fn render_menu(&mut self) {
+        let mut lines = vec![String::from("TETRIS"), String::new()];
+        for (i, mode) in self.menu_modes().into_iter().enumerate() {
+            let goal = format_goal(mode).map(|goal| format!(" ({goal})"));
+            lines.push(format!(
+                "{} {}{}",
+                i + 1,
+                mode.name(),
+                goal.unwrap_or_default()
+            ));
+        }
+        lines.push(String::from("Q to quit"));
+
+        self.view.blit(
+            &Sprite::new(Vec2D::new(2, 1), &lines.join("\n"), Modifier::None),
+            Wrapping::Ignore,
+        );
+        self.view.blit(
+            &Sprite::new(Vec2D::new(2, 9), &self.controls_help_text, Modifier::None),
+            Wrapping::Ignore,
+        );
+    }
+}
+
+impl MainLoopRoot for Game {
+    type InputDataType = Vec<Event>;
+
+    fn frame(&mut self, input_data: Option<Self::InputDataType>) {
+        self.keys.release_stale_keys();
+        let input_events = input_data.unwrap_or_default();
+
+        match self.screen {
+            Screen::Playing => self.play_frame(input_events),
+            Screen::Menu | Screen::GameOver(_) => self.menu_frame(input_events),
+        }
+    }
+
+    fn render_frame(&mut self) {
+        self.view.clear();
+
+        match self.screen {
+            Screen::Menu => self.render_menu(),
+            Screen::Playing => self.render_game(),
+            Screen::GameOver(reason) => self.render_game_over(reason),
+        }
 
-        execute!(stdout(), MoveTo(0, 0)).unwrap();
-        execute!(stdout(), Clear(ClearType::FromCursorDown)).unwrap();
+        execute!(stdout(), MoveTo(0, 0)).expect("Failed to move cursor");
+        execute!(stdout(), Clear(ClearType::FromCursorDown)).expect("Failed to clear screen");
         self.view
             .display_render()
             .expect("Failed to print render to screen");
@@ -224,10 +474,9 @@ impl MainLoopRoot for Game {
     fn sleep_and_get_input_data(
         &self,
         fps: f32,
-        elapsed: std::time::Duration,
+        elapsed: Duration,
     ) -> (bool, Option<Self::InputDataType>) {
-        Input::sleep_fps_and_get_input(fps, elapsed)
-            .exit_on_kb_interrupt()
-            .as_tuple()
+        let (frame_skip, events) = terminal::sleep_and_read_events(fps, elapsed);
+        (frame_skip, Some(events))
     }
 }
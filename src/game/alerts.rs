@@ -4,6 +4,8 @@ use gemini_engine::elements::{
     Pixel, Text, Vec2D,
 };
 
+use super::block_manager::{Spin, TSpin};
+
 const ALERT_LIFETIME: u16 = 20;
 
 pub fn generate_alert_for_filled_lines(cleared_lines: isize) -> Option<(isize, String)> {
@@ -12,51 +14,85 @@ pub fn generate_alert_for_filled_lines(cleared_lines: isize) -> Option<(isize, S
         2 => Some((300, String::from("Double!"))),
         3 => Some((500, String::from("Triple!"))),
         4 => Some((800, String::from("Tetris!"))),
-        0 => None,
-        _ => panic!("entered value should be between 0 and 4"),
+        // Only custom pieces can clear more than four lines at once
+        lines if lines > 4 => Some((200 * lines, format!("{lines} Lines!"))),
+        _ => None,
     }
 }
 
+/// Spins by pieces other than T are scored the same as full T-spins
+pub fn generate_alert_for_spin(spin: Spin, cleared_lines: isize) -> (isize, String) {
+    let (piece, is_mini) = match spin {
+        Spin::T(t_spin) => (String::from("T"), t_spin == TSpin::Mini),
+        Spin::Immobile(shape) => (shape.to_string(), false),
+    };
+
+    let (score, clear) = match (is_mini, cleared_lines) {
+        (false, 0) => (400, "!"),
+        (false, 1) => (800, " Single!"),
+        (false, 2) => (1200, " Double!"),
+        (false, 3) => (1600, " Triple!"),
+        (false, _) => (2000, " Quad!"),
+        (true, 0) => (100, " Mini!"),
+        (true, 1) => (200, " Mini Single!"),
+        (true, _) => (400, " Mini Double!"),
+    };
+    (score, format!("{piece}-Spin{clear}"))
+}
+
+/// The bonus for clearing every cell from the board, on top of the usual score for the clear
+pub fn generate_alert_for_perfect_clear(
+    cleared_lines: isize,
+    is_back_to_back: bool,
+) -> (isize, String) {
+    let score = match cleared_lines {
+        1 => 800,
+        2 => 1200,
+        3 => 1800,
+        _ if is_back_to_back => 3200,
+        _ => 2000,
+    };
+    (score, String::from("Perfect Clear!"))
+}
+
+/// Will pick the first existing alert score pair
+pub fn priorised_alert(alert_score_pairs: &[Option<(isize, String)>]) -> Option<(isize, String)> {
+    alert_score_pairs.iter().flatten().next().cloned()
+}
+
 pub struct AlertDisplay {
     pub pos: Vec2D,
+    /// Alerts longer than this are wrapped onto multiple lines
+    max_width: usize,
     alerts: Vec<(String, u16)>,
 }
 
 impl AlertDisplay {
-    pub const fn new(pos: Vec2D) -> Self {
+    pub const fn new(pos: Vec2D, max_width: usize) -> Self {
         Self {
             pos,
+            max_width,
             alerts: vec![],
         }
     }
 
     pub fn push(&mut self, alert: &str) {
-        self.alerts.push((String::from(alert), ALERT_LIFETIME));
-    }
-
-    pub fn handle_with_score(
-        &mut self,
-        score: &mut isize,
-        score_and_alert: Option<(isize, String)>,
-    ) {
-        if let Some((add_score, alert)) = score_and_alert {
-            *score += add_score;
-            self.push(&alert);
+        let mut line = String::new();
+        for word in alert.split(' ') {
+            if !line.is_empty() && line.len() + 1 + word.len() > self.max_width {
+                self.alerts.push((line, ALERT_LIFETIME));
+                line = String::new();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
         }
+        self.alerts.push((line, ALERT_LIFETIME));
     }
 
-    /// Will pick the first existing alert score pair and run `handle_with_score` on that
-    pub fn priorised_alerts_with_score(
-        &mut self,
-        alert_score_pairs: &[Option<(isize, String)>],
-        score: &mut isize,
-    ) {
-        for score_alert_pair in alert_score_pairs {
-            if score_alert_pair.is_some() {
-                self.handle_with_score(score, score_alert_pair.clone());
-                break;
-            }
-        }
+    pub fn clear(&mut self) {
+        self.alerts.clear();
     }
 
     pub fn frame(&mut self) {
@@ -97,6 +133,6 @@ impl ViewElement for AlertDisplay {
 
 impl Default for AlertDisplay {
     fn default() -> Self {
-        Self::new(Vec2D::ZERO)
+        Self::new(Vec2D::ZERO, usize::MAX)
     }
 }
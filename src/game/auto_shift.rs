@@ -0,0 +1,89 @@
+/// A shift the active block should make this frame because a shift key is being held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoShiftMove {
+    /// Move one cell in the given direction
+    Step(isize),
+    /// Move as far as possible in the given direction
+    ToWall(isize),
+}
+
+/// Delayed auto shift. Holding left or right moves the block once, waits `das` frames, then moves it again every `arr` frames
+pub struct AutoShift {
+    /// The direction of the most recently pressed shift key that is still held, `-1` for left and `1` for right
+    direction: Option<isize>,
+    /// How many frames the shift key has been held for, up to `das`
+    charge: u32,
+    /// Frames left until the next auto-repeat
+    repeat_cooldown: u32,
+    /// Frames left where auto-repeat is paused after a rotation
+    cut_remaining: u32,
+    // Constants
+    das: u32,
+    arr: u32,
+    das_cut_delay: u32,
+}
+
+impl AutoShift {
+    pub const fn new(das: u32, arr: u32, das_cut_delay: u32) -> Self {
+        Self {
+            direction: None,
+            charge: 0,
+            repeat_cooldown: 0,
+            cut_remaining: 0,
+            das,
+            arr,
+            das_cut_delay,
+        }
+    }
+
+    /// Call when a shift key is first pressed. The block should be moved once by the caller
+    pub const fn press(&mut self, direction: isize) {
+        self.direction = Some(direction);
+        self.charge = 0;
+        self.repeat_cooldown = 0;
+    }
+
+    /// Call when the block is rotated to pause auto-repeat for `das_cut_delay` frames
+    pub const fn cut(&mut self) {
+        self.cut_remaining = self.das_cut_delay;
+    }
+
+    /// Call once per frame with which shift keys are held. Returns the auto-repeat move to make this frame, if any
+    pub fn tick(&mut self, left_held: bool, right_held: bool) -> Option<AutoShiftMove> {
+        let is_held = |direction: isize| if direction < 0 { left_held } else { right_held };
+
+        // If the active shift key was let go, fall back to the other one if it is still held
+        if !self.direction.is_some_and(is_held) {
+            let fallback = self.direction.map_or(0, |direction| -direction);
+            self.direction = [fallback, -1, 1]
+                .into_iter()
+                .find(|direction| *direction != 0 && is_held(*direction));
+            self.charge = 0;
+            self.repeat_cooldown = 0;
+        }
+
+        let direction = self.direction?;
+
+        if self.charge < self.das {
+            self.charge += 1;
+            return None;
+        }
+
+        if self.cut_remaining > 0 {
+            self.cut_remaining -= 1;
+            return None;
+        }
+
+        if self.arr == 0 {
+            return Some(AutoShiftMove::ToWall(direction));
+        }
+
+        if self.repeat_cooldown > 0 {
+            self.repeat_cooldown -= 1;
+            return None;
+        }
+        self.repeat_cooldown = self.arr - 1;
+
+        Some(AutoShiftMove::Step(direction))
+    }
+}
@@ -1,129 +1,233 @@
-use gemini_engine::elements::{containers::CollisionContainer, PixelContainer, Vec2D};
+use std::{collections::VecDeque, rc::Rc};
+
+use gemini_engine::elements::{PixelContainer, Vec2D};
+use rand::RngCore;
 mod blocks;
-pub use blocks::{block_manipulation as tetris_core, Block, BlockType};
-use rand::Rng;
+mod randomizer;
+pub use blocks::{
+    block_manipulation::{self as tetris_core, RotationMove, Spin, TSpin},
+    rotation_system::{PieceSet, RotationSystem, RotationSystemType},
+    Block, BlockType, Rotation,
+};
+use randomizer::Randomizer;
+pub use randomizer::RandomizerType;
+
+use super::{board::Board, settings::LockReset};
 
 pub struct BlockManager {
-    bag: Vec<BlockType>,
+    randomizer: Box<dyn Randomizer>,
+    /// The upcoming pieces, in order
+    next_pieces: VecDeque<BlockType>,
     pub block: Block,
     pub ghost_block: Block,
     pub held_piece: Option<BlockType>,
     pub has_held: bool,
-    pub placing_cooldown: u32,
+    placing_cooldown: u32,
+    /// Number of times the lock delay has been reset by moving or rotating since the block last reached a new lowest row
+    move_resets: u32,
+    /// The lowest row the block's origin has reached
+    lowest_row: isize,
+    /// How the block last moved, if the last thing it did was rotate. Used to detect T-spins
+    last_rotation: Option<RotationMove>,
     // Constants
     piece_preview_count: usize,
     block_place_cooldown: u32,
+    lock_reset: LockReset,
+    spawn_pos: Vec2D,
+    rotation_system: Rc<dyn RotationSystem>,
 }
 
 impl BlockManager {
-    pub fn new(block_place_cooldown: u32, piece_preview_count: usize) -> Self {
+    pub fn new(
+        block_place_cooldown: u32,
+        lock_reset: LockReset,
+        piece_preview_count: usize,
+        spawn_pos: Vec2D,
+        rotation_system: Rc<dyn RotationSystem>,
+        randomizer: RandomizerType,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        let pieces = rotation_system.pieces();
+        // Replaced by the first piece dealt below
+        let placeholder = pieces[0];
         let mut tmp = Self {
-            bag: BlockType::bag()[0..rand::thread_rng().gen_range(1..8)].to_vec(),
-            block: Block::DEFAULT,
-            ghost_block: Block::DEFAULT,
+            randomizer: randomizer.build(pieces),
+            next_pieces: VecDeque::new(),
+            block: Block::new(placeholder, &rotation_system),
+            ghost_block: Block::new(placeholder, &rotation_system),
             held_piece: None,
             has_held: false,
             placing_cooldown: block_place_cooldown,
+            move_resets: 0,
+            lowest_row: spawn_pos.y,
+            last_rotation: None,
             block_place_cooldown,
+            lock_reset,
             piece_preview_count,
+            spawn_pos,
+            rotation_system,
         };
-        tmp.generate_new_block();
+        tmp.generate_new_block(rng);
         tmp
     }
 
-    pub fn reset_placing_cooldown(&mut self) {
+    /// Make a block of the given shape at the spawn position the active block, with a fresh lock delay
+    fn spawn_block(&mut self, shape: BlockType) {
+        self.block = Block::new(shape, &self.rotation_system);
+        self.block.pos = self.spawn_pos + self.rotation_system.spawn_offset(shape);
+        self.lowest_row = self.block.pos.y;
+        self.last_rotation = None;
+        self.move_resets = 0;
+        self.reset_placing_cooldown();
+    }
+
+    pub const fn reset_placing_cooldown(&mut self) {
         self.placing_cooldown = self.block_place_cooldown;
     }
 
+    /// Call after the block successfully moves or rotates, to reset the lock delay according to the lock reset rules
+    const fn on_block_moved(&mut self, was_shift_or_rotation: bool) {
+        if self.block.pos.y > self.lowest_row {
+            self.lowest_row = self.block.pos.y;
+            self.move_resets = 0;
+            if !matches!(self.lock_reset, LockReset::Never) {
+                self.reset_placing_cooldown();
+            }
+        } else if was_shift_or_rotation {
+            if let LockReset::OnMove { max_resets } = self.lock_reset {
+                if self.move_resets < max_resets {
+                    self.move_resets += 1;
+                    self.reset_placing_cooldown();
+                }
+            }
+        }
+    }
+
+    /// Call every frame the block is resting on the stack. Counts down the lock delay and returns true once the block should lock
+    pub const fn tick_lock_delay(&mut self) -> bool {
+        self.placing_cooldown = self.placing_cooldown.saturating_sub(1);
+
+        // Once out of move resets, a grounded block locks straight away
+        let out_of_resets = match self.lock_reset {
+            LockReset::OnMove { max_resets } => self.move_resets >= max_resets,
+            LockReset::OnStep | LockReset::Never => false,
+        };
+
+        self.placing_cooldown == 0 || out_of_resets
+    }
+
     /// Call when a block is placed
-    ///
-    /// Returns true if the block is placed at the very top of the board, indicating a lost game
-    pub fn reset(&mut self) -> bool {
-        self.reset_placing_cooldown();
+    pub const fn reset(&mut self) {
         self.has_held = false;
-        self.block.pos.y < 1
     }
 
-    pub fn generate_new_block(&mut self) {
-        let next_piece = self.bag.pop().unwrap_or_else(|| unreachable!());
-        if self.bag.len() <= self.piece_preview_count {
-            let mut new_bag = BlockType::bag().to_vec();
-            new_bag.extend(&self.bag);
-            self.bag.clear();
-            self.bag.extend(new_bag);
+    pub fn generate_new_block(&mut self, rng: &mut dyn RngCore) {
+        // Keep enough pieces queued up to spawn one and still fill the preview
+        while self.next_pieces.len() <= self.piece_preview_count {
+            let piece = self.randomizer.next_piece(rng);
+            self.next_pieces.push_back(piece);
         }
 
-        self.block = Block::new(next_piece);
+        let next_piece = self
+            .next_pieces
+            .pop_front()
+            .unwrap_or_else(|| unreachable!());
+        self.spawn_block(next_piece);
+    }
+
+    /// Attempt to move the block. Updates the lock delay and returns true if successful
+    pub fn try_move_block(&mut self, board: &Board, offset: Vec2D) -> bool {
+        let did_move = tetris_core::try_move_block(board, &mut self.block, offset);
+        if did_move {
+            self.last_rotation = None;
+            self.on_block_moved(offset.x != 0);
+        }
+        did_move
     }
 
-    /// Attempt to move the block. Resets the placing cooldown and returns true if successful
-    pub fn try_move_block(&mut self, collision: &CollisionContainer, offset: Vec2D) -> bool {
-        let did_move = tetris_core::try_move_block(collision, &mut self.block, offset);
+    /// Move the block as far as it will go horizontally. Counts as a single move for the lock delay. Returns true if it moved at all
+    pub fn shift_to_wall(&mut self, board: &Board, direction: isize) -> bool {
+        let mut did_move = false;
+        while tetris_core::try_move_block(board, &mut self.block, Vec2D::new(direction, 0)) {
+            did_move = true;
+        }
         if did_move {
-            self.reset_placing_cooldown();
+            self.last_rotation = None;
+            self.on_block_moved(true);
         }
         did_move
     }
 
-    /// Attempt to rotate the block. Resets the placing cooldown and returns true if successful
-    pub fn try_rotate_block(&mut self, collision: &CollisionContainer, clockwise: bool) {
-        let did_rotate = tetris_core::try_rotate_block(collision, &mut self.block, clockwise);
-        if did_rotate {
-            self.reset_placing_cooldown();
+    /// Attempt to rotate the block. Updates the lock delay and returns true if successful
+    pub fn try_rotate_block(&mut self, board: &Board, rotation: Rotation) {
+        if let Some(kick_index) = tetris_core::try_rotate_block(board, &mut self.block, rotation) {
+            self.last_rotation = Some(RotationMove {
+                rotation,
+                kick_index,
+            });
+            self.on_block_moved(true);
         }
     }
 
-    /// Hold the current block
-    pub fn hold(&mut self) {
-        if !self.has_held {
+    /// Rotate a block that has only just spawned, trying kicks as usual. Unlike [`Self::try_rotate_block`] this doesn't count as a move
+    pub fn initial_rotate(&mut self, board: &Board, rotation: Rotation) {
+        if tetris_core::try_rotate_block(board, &mut self.block, rotation).is_some() {
+            self.lowest_row = self.block.pos.y;
+        }
+    }
+
+    /// Hold the current block. Returns true if a new block was brought in
+    pub fn hold(&mut self, rng: &mut dyn RngCore) -> bool {
+        let can_hold = !self.has_held;
+        if can_hold {
             let current_held_piece = self.held_piece;
             self.held_piece = Some(self.block.shape);
             match current_held_piece {
-                Some(piece) => self.block = Block::new(piece),
+                Some(piece) => self.spawn_block(piece),
                 None => {
-                    self.generate_new_block();
+                    self.generate_new_block(rng);
                 }
             }
             self.has_held = true;
         }
+        can_hold
     }
 
-    pub fn generate_ghost_block(&mut self, collision: &CollisionContainer) {
+    pub fn generate_ghost_block(&mut self, board: &Board) {
         let mut ghost_block = self.block.clone();
         ghost_block.is_ghost = true;
 
-        while tetris_core::try_move_block(collision, &mut ghost_block, Vec2D::new(0, 1)) {}
+        while tetris_core::try_move_block(board, &mut ghost_block, Vec2D::new(0, 1)) {}
 
         self.ghost_block = ghost_block;
     }
 
-    /// Generate an appropriate alert
-    pub fn check_for_t_spin(
-        &self,
-        collision: &CollisionContainer,
-        cleared_lines: isize,
-    ) -> Option<(isize, String)> {
-        tetris_core::handle_t_spin(collision, &self.block, cleared_lines)
+    /// Check whether the block is in a spin. Pieces other than T only spin if `all_spin` is enabled. `board` should not include the block itself
+    pub fn check_for_spin(&self, board: &Board, all_spin: bool) -> Option<Spin> {
+        if self.block.shape == BlockType::T {
+            tetris_core::detect_t_spin(board, &self.block, self.last_rotation).map(Spin::T)
+        } else {
+            (all_spin && tetris_core::is_immobile_spin(board, &self.block, self.last_rotation))
+                .then_some(Spin::Immobile(self.block.shape))
+        }
     }
 
-    pub fn next_piece_display(&self) -> PixelContainer {
+    pub fn next_piece_display(&self, pos: Vec2D) -> PixelContainer {
         let mut container = PixelContainer::new();
-        for i in 0..self.piece_preview_count {
-            let mut next_block_display = Block::new(self.bag[self.bag.len() - i - 1]);
-            next_block_display.pos = Vec2D::new(15, 12 + i as isize * 3);
+        for (i, &piece) in self.next_pieces.iter().enumerate() {
+            let mut next_block_display = Block::new(piece, &self.rotation_system);
+            next_block_display.pos = pos + Vec2D::new(0, i as isize * 3);
             container.blit(&next_block_display);
         }
 
         container
     }
 
-    pub const fn held_piece_display(&self) -> Option<Block> {
-        if let Some(piece) = self.held_piece {
-            let mut held_block_display = Block::new(piece);
-            held_block_display.pos = Vec2D::new(15, 4);
-            Some(held_block_display)
-        } else {
-            None
-        }
+    pub fn held_piece_display(&self, pos: Vec2D) -> Option<Block> {
+        self.held_piece.map(|piece| {
+            let mut held_block_display = Block::new(piece, &self.rotation_system);
+            held_block_display.pos = pos;
+            held_block_display
+        })
     }
 }
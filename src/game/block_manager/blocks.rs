@@ -1,16 +1,29 @@
-use std::collections::HashMap;
+use std::{
+    fmt::{self, Display},
+    rc::Rc,
+};
 
-use gemini_engine::elements::view::{utils, ColChar, Pixel, Vec2D, ViewElement};
-mod block_data;
+use gemini_engine::elements::view::{utils, ColChar, Colour, Pixel, Vec2D, ViewElement};
 pub mod block_manipulation;
-use block_data::BlockData;
-use rand::seq::SliceRandom;
-
-const fn bool_to_polarity(value: bool) -> isize {
-    if value {
-        1
-    } else {
-        -1
+pub mod rotation_system;
+use rotation_system::RotationSystem;
+
+/// A direction to turn a block in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Clockwise,
+    Anticlockwise,
+    Half,
+}
+
+impl Rotation {
+    /// How many clockwise quarter turns this rotation makes
+    const fn quarter_turns(self) -> isize {
+        match self {
+            Self::Clockwise => 1,
+            Self::Anticlockwise => -1,
+            Self::Half => 2,
+        }
     }
 }
 
@@ -23,10 +36,12 @@ pub enum BlockType {
     S,
     T,
     Z,
+    /// Any other piece from a piece file, identified by its position in the file
+    Custom(usize),
 }
 
 impl BlockType {
-    const ALL_VARIANTS: [Self; 7] = [
+    pub(super) const TETROMINOES: [Self; 7] = [
         Self::I,
         Self::J,
         Self::L,
@@ -35,21 +50,42 @@ impl BlockType {
         Self::T,
         Self::Z,
     ];
-    pub fn bag() -> [Self; 7] {
-        let mut variants = Self::ALL_VARIANTS;
-        variants.shuffle(&mut rand::thread_rng());
-        variants
-    }
 
-    fn get_rotation_states(self) -> Vec<Vec<Vec2D>> {
-        BlockData::from(self).rotation_states
+    /// The tetromino with the given name, or a custom piece if there isn't one
+    fn from_name(name: &str, index: usize) -> Self {
+        match name {
+            "I" => Self::I,
+            "J" => Self::J,
+            "L" => Self::L,
+            "O" => Self::O,
+            "S" => Self::S,
+            "T" => Self::T,
+            "Z" => Self::Z,
+            _ => Self::Custom(index),
+        }
     }
-    fn get_colour(self) -> ColChar {
-        // ColChar::EMPTY.with_char('▒') // Colourless
-        ColChar::SOLID.with_colour(BlockData::from(self).colour)
+
+    /// The standard colour of the piece. Custom pieces have no standard colour so are white
+    const fn guideline_colour(self) -> Colour {
+        match self {
+            Self::I => Colour::rgb(0, 255, 255),
+            Self::J => Colour::rgb(0, 0, 255),
+            Self::L => Colour::rgb(255, 165, 0),
+            Self::O => Colour::rgb(255, 255, 0),
+            Self::S => Colour::rgb(0, 255, 0),
+            Self::T => Colour::rgb(255, 0, 255),
+            Self::Z => Colour::rgb(255, 0, 0),
+            Self::Custom(_) => Colour::greyscale(255),
+        }
     }
-    pub(super) fn get_wall_kick_data(self) -> HashMap<(usize, usize), Vec<Vec2D>> {
-        BlockData::from(self).wall_kick_data
+}
+
+impl Display for BlockType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Custom(_) => write!(f, "Custom"),
+            shape => write!(f, "{shape:?}"),
+        }
     }
 }
 
@@ -59,32 +95,40 @@ pub struct Block {
     pub shape: BlockType,
     pub rotation: usize,
     pub(super) is_ghost: bool,
+    rotation_system: Rc<dyn RotationSystem>,
 }
 
 impl Block {
-    pub const DEFAULT: Self = Self::new(BlockType::O);
-
-    pub const fn new(shape: BlockType) -> Self {
+    /// Create a block in its spawn orientation, shaped by the given rotation system
+    pub fn new(shape: BlockType, rotation_system: &Rc<dyn RotationSystem>) -> Self {
         Self {
-            pos: Vec2D::new(5, 0),
+            pos: Vec2D::ZERO,
             shape,
             rotation: 0,
             is_ghost: false,
+            rotation_system: Rc::clone(rotation_system),
         }
     }
 
+    pub fn colour(&self) -> Colour {
+        self.rotation_system.colour(self.shape)
+    }
+
+    fn rotation_states(&self) -> &[Vec<Vec2D>] {
+        self.rotation_system.rotation_states(self.shape)
+    }
     fn rot_state_len(&self) -> isize {
-        self.shape.get_rotation_states().len() as isize
+        self.rotation_states().len() as isize
     }
-    pub fn get_rotation_indexes(&self, clockwise: bool) -> (usize, usize) {
+    pub fn get_rotation_indexes(&self, rotation: Rotation) -> (usize, usize) {
         (
             self.rotation,
-            (self.rotation as isize + bool_to_polarity(clockwise)).rem_euclid(self.rot_state_len())
+            (self.rotation as isize + rotation.quarter_turns()).rem_euclid(self.rot_state_len())
                 as usize,
         )
     }
-    pub fn rotate(&mut self, clockwise: bool) {
-        self.rotation = (self.rotation as isize + bool_to_polarity(clockwise))
+    pub fn rotate(&mut self, rotation: Rotation) {
+        self.rotation = (self.rotation as isize + rotation.quarter_turns())
             .rem_euclid(self.rot_state_len()) as usize;
     }
 }
@@ -96,17 +140,19 @@ impl Clone for Block {
             shape: self.shape,
             rotation: self.rotation,
             is_ghost: false,
+            rotation_system: Rc::clone(&self.rotation_system),
         }
     }
 }
 
 impl ViewElement for Block {
     fn active_pixels(&self) -> Vec<Pixel> {
-        let rotation_states = self.shape.get_rotation_states();
+        let rotation_states = self.rotation_states();
         let block_colour = if self.is_ghost {
             ColChar::BACKGROUND
         } else {
-            self.shape.get_colour()
+            // ColChar::EMPTY.with_char('▒') // Colourless
+            ColChar::SOLID.with_colour(self.colour())
         };
 
         let block_points: Vec<Vec2D> = rotation_states
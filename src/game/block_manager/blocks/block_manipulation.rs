@@ -1,13 +1,35 @@
-use gemini_engine::elements::{
-    containers::CollisionContainer,
-    view::{utils, ViewElement},
-    Vec2D,
-};
+use gemini_engine::elements::{view::ViewElement, Vec2D};
 
-use super::{Block, BlockType};
+use super::{Block, BlockType, Rotation};
+use crate::game::board::Board;
 
-pub fn try_move_block(collision: &CollisionContainer, block: &mut Block, offset: Vec2D) -> bool {
-    let did_move = !collision.will_overlap_element(block, offset);
+/// In SRS, the fifth kick test is the one that lets a T into a T-spin triple or fin slot, which counts as a full T-spin even if it would otherwise be a Mini
+const T_SPIN_TRIPLE_KICK_INDEX: usize = 4;
+
+/// A successful rotation, and which of the rotation system's kicks it needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationMove {
+    pub rotation: Rotation,
+    pub kick_index: usize,
+}
+
+/// The kinds of T-spin a locked T can make
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TSpin {
+    Full,
+    Mini,
+}
+
+/// A spin made by the block that just locked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spin {
+    T(TSpin),
+    /// Any other piece that rotated into a spot it can't move left, right or up out of
+    Immobile(BlockType),
+}
+
+pub fn try_move_block(board: &Board, block: &mut Block, offset: Vec2D) -> bool {
+    let did_move = !board.will_overlap(block, offset);
     if did_move {
         block.pos += offset;
     }
@@ -15,80 +37,282 @@ pub fn try_move_block(collision: &CollisionContainer, block: &mut Block, offset:
     did_move
 }
 
-pub fn try_rotate_block(
-    collision: &CollisionContainer,
-    block: &mut Block,
-    clockwise: bool,
-) -> bool {
-    if block.shape == BlockType::O {
-        return false;
+/// Rotate the block, trying each of the rotation system's kicks in turn. Returns the index of the kick used, or `None` if the block couldn't rotate
+pub fn try_rotate_block(board: &Board, block: &mut Block, rotation: Rotation) -> Option<usize> {
+    // Rotating into the same state, such as any rotation of an O, does nothing
+    let (from, to) = block.get_rotation_indexes(rotation);
+    if from == to {
+        return None;
     }
 
-    let rotation_index = block.get_rotation_indexes(clockwise);
+    let kicks = block.rotation_system.kicks(board, block, rotation);
     let mut hypothetical_block = block.clone();
-    hypothetical_block.rotate(clockwise);
+    hypothetical_block.rotate(rotation);
 
-    let mut did_move = false;
-    for possible_offset in &block.shape.get_wall_kick_data()[&rotation_index] {
+    for (kick_index, possible_offset) in kicks.iter().enumerate() {
         hypothetical_block.pos = block.pos + *possible_offset;
-        if !collision.overlaps_element(&hypothetical_block) {
-            did_move = true;
+        if !board.overlaps(&hypothetical_block) {
             block.pos += *possible_offset;
-            block.rotate(clockwise);
-            break;
+            block.rotate(rotation);
+            return Some(kick_index);
         }
     }
 
-    did_move
+    None
 }
 
-pub fn handle_t_spin(
-    collision: &CollisionContainer,
-    block: &Block,
-    cleared_lines: isize,
-) -> Option<(isize, String)> {
-    if block.shape == BlockType::T {
-        let collision_pixels = utils::pixels_to_points(&collision.active_pixels());
-
-        let positions_to_check: Vec<Vec2D> = [
-            Vec2D::new(1, 1),   // Top-left
-            Vec2D::new(1, -1),  // Top-right
-            Vec2D::new(-1, 1),  // Bottom-left
-            Vec2D::new(-1, -1), // Top-left
-        ]
+/// Find the centre cell of a T, and the direction its point faces
+fn t_centre_and_facing(block: &Block) -> Option<(Vec2D, Vec2D)> {
+    let cells = block.active_points();
+    if cells.len() != 4 {
+        return None;
+    }
+
+    let directions = [
+        Vec2D::new(0, -1),
+        Vec2D::new(1, 0),
+        Vec2D::new(0, 1),
+        Vec2D::new(-1, 0),
+    ];
+    // The centre is the only cell with three neighbours, and the missing neighbour is behind the T's flat side
+    let centre = *cells.iter().find(|cell| {
+        directions
+            .iter()
+            .filter(|direction| cells.contains(&(**cell + **direction)))
+            .count()
+            == 3
+    })?;
+    let back = directions
         .into_iter()
-        .map(|o| block.pos + o)
-        .collect();
-        let mut counted_positions = 0;
-        for pos in &positions_to_check {
-            if collision_pixels.contains(pos) {
-                counted_positions += 1;
-            }
-        }
+        .find(|direction| !cells.contains(&(centre + *direction)))?;
 
-        let blocked_from_top_right = if let 0..=2 = block.rotation {
-            collision_pixels.contains(&positions_to_check[1])
-        } else {
-            false
-        };
-        let blocked_from_top_left = if let 0 | 2 | 3 = block.rotation {
-            collision_pixels.contains(&positions_to_check[3])
-        } else {
-            false
-        };
-
-        if counted_positions > 2 && (blocked_from_top_left || blocked_from_top_right) {
-            Some(match cleared_lines {
-                0 => (400, String::from("T-Spin!")),
-                1 => (800, String::from("T-Spin Single!")),
-                2 => (1200, String::from("T-Spin Double!")),
-                3 => (1600, String::from("T-Spin Triple!")),
-                _ => (200, String::from("T-Spin?")),
-            })
-        } else {
-            None
-        }
+    Some((centre, Vec2D::new(-back.x, -back.y)))
+}
+
+/// Guideline 3-corner T-spin detection. The T must have got into place by rotating, and at least three of the four cells diagonal to its centre must be filled (walls and the floor count). It is a full T-spin if both corners either side of its point are filled, or if it rotated in with the T-spin triple kick, and a Mini otherwise
+///
+/// `board` should not include the block itself
+pub fn detect_t_spin(
+    board: &Board,
+    block: &Block,
+    last_rotation: Option<RotationMove>,
+) -> Option<TSpin> {
+    let last_rotation = last_rotation?;
+    if block.shape != BlockType::T {
+        return None;
+    }
+
+    let (centre, facing) = t_centre_and_facing(block)?;
+    let side = Vec2D::new(facing.y, facing.x);
+    let filled_corners = |corners: [Vec2D; 2]| {
+        corners
+            .into_iter()
+            .filter(|corner| board.is_occupied(*corner))
+            .count()
+    };
+    let front_corners = filled_corners([centre + facing + side, centre + facing - side]);
+    let back_corners = filled_corners([centre - facing + side, centre - facing - side]);
+
+    if front_corners + back_corners < 3 {
+        return None;
+    }
+
+    let used_t_spin_triple_kick = last_rotation.rotation != Rotation::Half
+        && last_rotation.kick_index == T_SPIN_TRIPLE_KICK_INDEX;
+    if front_corners == 2 || used_t_spin_triple_kick {
+        Some(TSpin::Full)
     } else {
-        None
+        Some(TSpin::Mini)
+    }
+}
+
+/// Returns true if the last thing the block did was rotate, and it can't now move left, right or up. All-spin rulesets count this as a spin for any piece
+///
+/// `board` should not include the block itself
+pub fn is_immobile_spin(board: &Board, block: &Block, last_rotation: Option<RotationMove>) -> bool {
+    last_rotation.is_some()
+        && [Vec2D::new(-1, 0), Vec2D::new(1, 0), Vec2D::new(0, -1)]
+            .into_iter()
+            .all(|offset| board.will_overlap(block, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::game::block_manager::{PieceSet, RotationSystem};
+
+    /// A rotation that didn't need a kick
+    const ROTATED: Option<RotationMove> = Some(RotationMove {
+        rotation: Rotation::Clockwise,
+        kick_index: 0,
+    });
+
+    /// An SRS T with its centre at `pos`
+    fn t_block(pos: Vec2D, rotation: usize) -> Block {
+        let rotation_system: Rc<dyn RotationSystem> = Rc::new(PieceSet::srs());
+        let mut block = Block::new(BlockType::T, &rotation_system);
+        block.pos = pos;
+        block.rotation = rotation;
+        block
+    }
+
+    #[test]
+    fn t_spin_double() {
+        #[rustfmt::skip]
+        let board = Board::from_rows(&[
+            "......",
+            "#.....",
+            "...###",
+            "#.####",
+        ]);
+        let block = t_block(Vec2D::new(1, 2), 2);
+
+        assert_eq!(detect_t_spin(&board, &block, ROTATED), Some(TSpin::Full));
+    }
+
+    #[test]
+    fn dropping_into_a_slot_is_not_a_t_spin() {
+        #[rustfmt::skip]
+        let board = Board::from_rows(&[
+            "......",
+            "#.....",
+            "...###",
+            "#.####",
+        ]);
+        let block = t_block(Vec2D::new(1, 2), 2);
+
+        assert_eq!(detect_t_spin(&board, &block, None), None);
+    }
+
+    #[test]
+    fn two_corners_is_not_a_t_spin() {
+        #[rustfmt::skip]
+        let board = Board::from_rows(&[
+            "......",
+            "......",
+            "...###",
+            "#.####",
+        ]);
+        let block = t_block(Vec2D::new(1, 2), 2);
+
+        assert_eq!(detect_t_spin(&board, &block, ROTATED), None);
+    }
+
+    #[test]
+    fn t_spin_mini_against_wall() {
+        // Pointing right with only one of its front corners filled, with the wall filling both back corners
+        #[rustfmt::skip]
+        let board = Board::from_rows(&[
+            "....",
+            "....",
+            "....",
+            ".###",
+        ]);
+        let block = t_block(Vec2D::new(0, 2), 1);
+
+        assert_eq!(detect_t_spin(&board, &block, ROTATED), Some(TSpin::Mini));
+    }
+
+    #[test]
+    fn t_spin_triple_kick_upgrades_mini() {
+        #[rustfmt::skip]
+        let board = Board::from_rows(&[
+            "....",
+            "....",
+            "....",
+            ".###",
+        ]);
+        let block = t_block(Vec2D::new(0, 2), 1);
+
+        let t_spin_triple_kick = Some(RotationMove {
+            rotation: Rotation::Clockwise,
+            kick_index: T_SPIN_TRIPLE_KICK_INDEX,
+        });
+        assert_eq!(
+            detect_t_spin(&board, &block, t_spin_triple_kick),
+            Some(TSpin::Full)
+        );
+
+        // 180 kicks never upgrade
+        let half_turn_kick = Some(RotationMove {
+            rotation: Rotation::Half,
+            kick_index: T_SPIN_TRIPLE_KICK_INDEX,
+        });
+        assert_eq!(
+            detect_t_spin(&board, &block, half_turn_kick),
+            Some(TSpin::Mini)
+        );
+    }
+
+    #[test]
+    fn t_spin_triple() {
+        #[rustfmt::skip]
+        let mut board = Board::from_rows(&[
+            ".....",
+            "..#..",
+            ".....",
+            "##.##",
+            "#..##",
+            "##.##",
+        ]);
+        let mut block = t_block(Vec2D::new(1, 2), 0);
+
+        let kick_index = try_rotate_block(&board, &mut block, Rotation::Anticlockwise);
+        assert_eq!(kick_index, Some(T_SPIN_TRIPLE_KICK_INDEX));
+        assert_eq!(block.pos, Vec2D::new(2, 4));
+
+        let last_rotation = kick_index.map(|kick_index| RotationMove {
+            rotation: Rotation::Anticlockwise,
+            kick_index,
+        });
+        assert_eq!(
+            detect_t_spin(&board, &block, last_rotation),
+            Some(TSpin::Full)
+        );
+        board.place(&block);
+        assert_eq!(board.clear_filled_lines(), 3);
+    }
+
+    #[test]
+    fn immobile_s_spin() {
+        let rotation_system: Rc<dyn RotationSystem> = Rc::new(PieceSet::srs());
+        #[rustfmt::skip]
+        let board = Board::from_rows(&[
+            "....",
+            "#..#",
+            "..##",
+        ]);
+        let mut block = Block::new(BlockType::S, &rotation_system);
+        block.pos = Vec2D::new(1, 2);
+
+        assert!(is_immobile_spin(&board, &block, ROTATED));
+        assert!(!is_immobile_spin(&board, &block, None));
+
+        // Without the overhang, the S could have been dropped in
+        #[rustfmt::skip]
+        let board = Board::from_rows(&[
+            "....",
+            "...#",
+            "..##",
+        ]);
+        assert!(!is_immobile_spin(&board, &block, ROTATED));
+    }
+
+    #[test]
+    fn other_pieces_never_t_spin() {
+        let rotation_system: Rc<dyn RotationSystem> = Rc::new(PieceSet::srs());
+        #[rustfmt::skip]
+        let board = Board::from_rows(&[
+            "....",
+            "....",
+            "#..#",
+            "#..#",
+        ]);
+        let mut block = Block::new(BlockType::O, &rotation_system);
+        block.pos = Vec2D::new(1, 3);
+
+        assert_eq!(detect_t_spin(&board, &block, ROTATED), None);
     }
 }
@@ -0,0 +1,67 @@
+use std::{fmt::Debug, rc::Rc};
+
+use gemini_engine::elements::{view::Colour, Vec2D};
+
+use super::{Block, BlockType, Rotation};
+use crate::game::board::Board;
+mod ars;
+mod nrs;
+mod piece_set;
+pub use ars::Ars;
+pub use nrs::Nrs;
+pub use piece_set::PieceSet;
+
+/// Decides which pieces there are, what shape each has in every orientation, and where a rotated piece may kick to if it doesn't fit
+pub trait RotationSystem: Debug {
+    /// Every piece that can be dealt
+    fn pieces(&self) -> Vec<BlockType> {
+        BlockType::TETROMINOES.to_vec()
+    }
+
+    fn colour(&self, shape: BlockType) -> Colour {
+        shape.guideline_colour()
+    }
+
+    /// Where the piece spawns, relative to the board's spawn position
+    fn spawn_offset(&self, _shape: BlockType) -> Vec2D {
+        Vec2D::ZERO
+    }
+
+    /// The cells of each orientation of the piece in clockwise order, as offsets from the block's position. The first orientation is the one the piece spawns in
+    fn rotation_states(&self, shape: BlockType) -> &[Vec<Vec2D>];
+
+    /// The offsets to try moving the block by when rotating it, in order of preference. The rotation fails if none of them fit
+    fn kicks(&self, board: &Board, block: &Block, rotation: Rotation) -> Vec<Vec2D>;
+}
+
+/// The rotation systems to choose from when starting a game
+#[derive(Debug, Clone)]
+pub enum RotationSystemType {
+    /// The Super Rotation System used by modern guideline games
+    Srs,
+    /// The Arika Rotation System from the Tetris The Grand Master series
+    Ars,
+    /// The Nintendo Rotation System from NES Tetris
+    Nrs,
+    /// Pieces loaded from a piece file
+    Custom(PieceSet),
+}
+
+impl RotationSystemType {
+    pub fn build(&self) -> Rc<dyn RotationSystem> {
+        match self {
+            Self::Srs => Rc::new(PieceSet::srs()),
+            Self::Ars => Rc::new(Ars::new()),
+            Self::Nrs => Rc::new(Nrs::new()),
+            Self::Custom(piece_set) => Rc::new(piece_set.clone()),
+        }
+    }
+}
+
+/// Turn a compact table of cell offsets into rotation states
+fn states_from_table(table: &[[(isize, isize); 4]]) -> Vec<Vec<Vec2D>> {
+    table
+        .iter()
+        .map(|cells| cells.iter().map(|&(x, y)| Vec2D::new(x, y)).collect())
+        .collect()
+}
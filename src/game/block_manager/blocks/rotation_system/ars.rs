@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use gemini_engine::elements::{view::ViewElement, Vec2D};
+
+use super::{
+    super::{Block, BlockType, Rotation},
+    states_from_table, RotationSystem,
+};
+use crate::game::board::Board;
+
+/// The Arika Rotation System from the Tetris The Grand Master series. Pieces spawn flat side up and rest on the bottom of their bounding box in every orientation. A blocked rotation may kick one cell right or left, but never up off the floor, and the I piece never kicks
+#[derive(Debug)]
+pub struct Ars {
+    rotation_states: HashMap<BlockType, Vec<Vec<Vec2D>>>,
+}
+
+impl Ars {
+    pub fn new() -> Self {
+        Self {
+            rotation_states: HashMap::from([
+                (
+                    BlockType::I,
+                    states_from_table(&[
+                        [(-1, 0), (0, 0), (1, 0), (2, 0)],
+                        [(1, -1), (1, 0), (1, 1), (1, 2)],
+                    ]),
+                ),
+                (
+                    BlockType::J,
+                    states_from_table(&[
+                        [(-1, -1), (0, -1), (1, -1), (1, 0)],
+                        [(0, -2), (0, -1), (-1, 0), (0, 0)],
+                        [(-1, -1), (-1, 0), (0, 0), (1, 0)],
+                        [(0, -2), (1, -2), (0, -1), (0, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::L,
+                    states_from_table(&[
+                        [(-1, -1), (0, -1), (1, -1), (-1, 0)],
+                        [(-1, -2), (0, -2), (0, -1), (0, 0)],
+                        [(1, -1), (-1, 0), (0, 0), (1, 0)],
+                        [(0, -2), (0, -1), (0, 0), (1, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::O,
+                    states_from_table(&[[(0, -1), (1, -1), (0, 0), (1, 0)]]),
+                ),
+                (
+                    BlockType::S,
+                    states_from_table(&[
+                        [(0, -1), (1, -1), (-1, 0), (0, 0)],
+                        [(-1, -2), (-1, -1), (0, -1), (0, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::T,
+                    states_from_table(&[
+                        [(-1, -1), (0, -1), (1, -1), (0, 0)],
+                        [(0, -2), (-1, -1), (0, -1), (0, 0)],
+                        [(0, -1), (-1, 0), (0, 0), (1, 0)],
+                        [(0, -2), (0, -1), (1, -1), (0, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::Z,
+                    states_from_table(&[
+                        [(-1, -1), (0, -1), (0, 0), (1, 0)],
+                        [(1, -2), (0, -1), (1, -1), (0, 0)],
+                    ]),
+                ),
+            ]),
+        }
+    }
+}
+
+/// The centre column rule for J, L and T: if the first cell blocking the rotated piece, in reading order, is in the piece's centre column, the rotation may not kick
+fn is_blocked_in_centre_column(board: &Board, block: &Block, rotation: Rotation) -> bool {
+    let mut rotated_block = block.clone();
+    rotated_block.rotate(rotation);
+
+    let mut cells = rotated_block.active_points();
+    cells.sort_by_key(|cell| (cell.y, cell.x));
+
+    cells
+        .into_iter()
+        .find(|cell| board.is_occupied(*cell))
+        .is_some_and(|cell| cell.x == block.pos.x)
+}
+
+impl RotationSystem for Ars {
+    fn rotation_states(&self, shape: BlockType) -> &[Vec<Vec2D>] {
+        &self.rotation_states[&shape]
+    }
+
+    fn kicks(&self, board: &Board, block: &Block, rotation: Rotation) -> Vec<Vec2D> {
+        let can_kick = match block.shape {
+            BlockType::I | BlockType::O => false,
+            BlockType::J | BlockType::L | BlockType::T => {
+                !is_blocked_in_centre_column(board, block, rotation)
+            }
+            BlockType::S | BlockType::Z | BlockType::Custom(_) => true,
+        };
+
+        if can_kick {
+            vec![Vec2D::ZERO, Vec2D::new(1, 0), Vec2D::new(-1, 0)]
+        } else {
+            vec![Vec2D::ZERO]
+        }
+    }
+}
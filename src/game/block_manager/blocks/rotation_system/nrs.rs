@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use gemini_engine::elements::Vec2D;
+
+use super::{
+    super::{Block, BlockType, Rotation},
+    states_from_table, RotationSystem,
+};
+use crate::game::board::Board;
+
+/// The Nintendo Rotation System from NES Tetris. Pieces spawn flat side up, S, Z and I only have two orientations and sit right of centre when vertical, and nothing ever kicks
+#[derive(Debug)]
+pub struct Nrs {
+    rotation_states: HashMap<BlockType, Vec<Vec<Vec2D>>>,
+}
+
+impl Nrs {
+    pub fn new() -> Self {
+        Self {
+            rotation_states: HashMap::from([
+                (
+                    BlockType::I,
+                    states_from_table(&[
+                        [(-2, -1), (-1, -1), (0, -1), (1, -1)],
+                        [(0, -3), (0, -2), (0, -1), (0, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::J,
+                    states_from_table(&[
+                        [(-1, -1), (0, -1), (1, -1), (1, 0)],
+                        [(0, -2), (0, -1), (-1, 0), (0, 0)],
+                        [(-1, -2), (-1, -1), (0, -1), (1, -1)],
+                        [(0, -2), (1, -2), (0, -1), (0, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::L,
+                    states_from_table(&[
+                        [(-1, -1), (0, -1), (1, -1), (-1, 0)],
+                        [(-1, -2), (0, -2), (0, -1), (0, 0)],
+                        [(1, -2), (-1, -1), (0, -1), (1, -1)],
+                        [(0, -2), (0, -1), (0, 0), (1, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::O,
+                    states_from_table(&[[(-1, -1), (0, -1), (-1, 0), (0, 0)]]),
+                ),
+                (
+                    BlockType::S,
+                    states_from_table(&[
+                        [(0, -1), (1, -1), (-1, 0), (0, 0)],
+                        [(0, -2), (0, -1), (1, -1), (1, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::T,
+                    states_from_table(&[
+                        [(-1, -1), (0, -1), (1, -1), (0, 0)],
+                        [(0, -2), (-1, -1), (0, -1), (0, 0)],
+                        [(0, -2), (-1, -1), (0, -1), (1, -1)],
+                        [(0, -2), (0, -1), (1, -1), (0, 0)],
+                    ]),
+                ),
+                (
+                    BlockType::Z,
+                    states_from_table(&[
+                        [(-1, -1), (0, -1), (0, 0), (1, 0)],
+                        [(1, -2), (0, -1), (1, -1), (0, 0)],
+                    ]),
+                ),
+            ]),
+        }
+    }
+}
+
+impl RotationSystem for Nrs {
+    fn rotation_states(&self, shape: BlockType) -> &[Vec<Vec2D>] {
+        &self.rotation_states[&shape]
+    }
+
+    fn kicks(&self, _board: &Board, _block: &Block, _rotation: Rotation) -> Vec<Vec2D> {
+        vec![Vec2D::ZERO]
+    }
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use gemini_engine::elements::{view::Colour, Vec2D};
+use serde::Deserialize;
+
+use super::{
+    super::{Block, BlockType, Rotation},
+    RotationSystem,
+};
+use crate::game::board::Board;
+
+const SRS_PIECE_FILE: &str = include_str!("srs.toml");
+
+/// A point as written in a piece file, `[x, y]`
+type Point = [isize; 2];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PieceFile {
+    piece: Vec<PieceEntry>,
+    #[serde(default)]
+    kick_tables: HashMap<String, HashMap<String, Vec<Point>>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PieceEntry {
+    name: String,
+    colour: [u8; 3],
+    #[serde(default)]
+    spawn_offset: Point,
+    rotation_states: Vec<Vec<Point>>,
+    #[serde(default)]
+    kick_tables: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct PieceDefinition {
+    colour: Colour,
+    spawn_offset: Vec2D,
+    rotation_states: Vec<Vec<Vec2D>>,
+    wall_kick_data: HashMap<(usize, usize), Vec<Vec2D>>,
+}
+
+/// A set of pieces with their shapes, colours and kick tables all defined by a piece file. See `srs.toml` for the format
+#[derive(Debug, Clone)]
+pub struct PieceSet {
+    pieces: Vec<BlockType>,
+    definitions: HashMap<BlockType, PieceDefinition>,
+}
+
+const fn to_vec2d(point: Point) -> Vec2D {
+    Vec2D::new(point[0], point[1])
+}
+
+/// Parse a rotation written as `"<from>><to>"`, checking both states exist
+fn parse_rotation(rotation: &str, state_count: usize) -> Option<(usize, usize)> {
+    let (from, to) = rotation.split_once('>')?;
+    let (from, to) = (from.trim().parse().ok()?, to.trim().parse().ok()?);
+
+    (from < state_count && to < state_count).then_some((from, to))
+}
+
+impl PieceSet {
+    /// The pieces of the Super Rotation System
+    pub fn srs() -> Self {
+        Self::from_toml(SRS_PIECE_FILE).expect("the built-in SRS piece file should be valid")
+    }
+
+    /// Load a piece set from a TOML piece file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+
+        Self::from_toml(&contents)
+            .map_err(|err| format!("Invalid piece file {}: {err}", path.display()))
+    }
+
+    fn from_toml(contents: &str) -> Result<Self, String> {
+        let file: PieceFile = toml::from_str(contents).map_err(|err| err.to_string())?;
+        if file.piece.is_empty() {
+            return Err(String::from("there must be at least one piece"));
+        }
+
+        let mut pieces = vec![];
+        let mut definitions = HashMap::new();
+        for (i, entry) in file.piece.into_iter().enumerate() {
+            let shape = BlockType::from_name(&entry.name, i);
+            if definitions.contains_key(&shape) {
+                return Err(format!("there is more than one piece named {}", entry.name));
+            }
+            if entry.rotation_states.is_empty() || entry.rotation_states.iter().any(Vec::is_empty) {
+                return Err(format!("piece {} has an empty rotation state", entry.name));
+            }
+
+            let state_count = entry.rotation_states.len();
+            let mut wall_kick_data = HashMap::new();
+            for table_name in &entry.kick_tables {
+                let table = file.kick_tables.get(table_name).ok_or_else(|| {
+                    format!(
+                        "piece {} uses a missing kick table: {table_name}",
+                        entry.name
+                    )
+                })?;
+
+                for (rotation, kicks) in table {
+                    let rotation = parse_rotation(rotation, state_count).ok_or_else(|| {
+                        format!(
+                            "kick table {table_name} has a rotation piece {} can't make: {rotation}",
+                            entry.name
+                        )
+                    })?;
+                    wall_kick_data.insert(rotation, kicks.iter().copied().map(to_vec2d).collect());
+                }
+            }
+
+            pieces.push(shape);
+            definitions.insert(
+                shape,
+                PieceDefinition {
+                    colour: Colour::rgb(entry.colour[0], entry.colour[1], entry.colour[2]),
+                    spawn_offset: to_vec2d(entry.spawn_offset),
+                    rotation_states: entry
+                        .rotation_states
+                        .into_iter()
+                        .map(|state| state.into_iter().map(to_vec2d).collect())
+                        .collect(),
+                    wall_kick_data,
+                },
+            );
+        }
+
+        Ok(Self {
+            pieces,
+            definitions,
+        })
+    }
+}
+
+impl RotationSystem for PieceSet {
+    fn pieces(&self) -> Vec<BlockType> {
+        self.pieces.clone()
+    }
+
+    fn colour(&self, shape: BlockType) -> Colour {
+        self.definitions[&shape].colour
+    }
+
+    fn spawn_offset(&self, shape: BlockType) -> Vec2D {
+        self.definitions[&shape].spawn_offset
+    }
+
+    fn rotation_states(&self, shape: BlockType) -> &[Vec<Vec2D>] {
+        &self.definitions[&shape].rotation_states
+    }
+
+    /// Rotations without an entry in the piece's kick tables don't kick
+    fn kicks(&self, _board: &Board, block: &Block, rotation: Rotation) -> Vec<Vec2D> {
+        self.definitions[&block.shape]
+            .wall_kick_data
+            .get(&block.get_rotation_indexes(rotation))
+            .cloned()
+            .unwrap_or_else(|| vec![Vec2D::ZERO])
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use super::BlockType;
+
+/// Decides which piece comes next
+pub trait Randomizer {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> BlockType;
+}
+
+/// The piece generators to choose from when starting a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomizerType {
+    /// Deal every piece once in a random order, then repeat. Used by guideline games
+    SevenBag,
+    /// Like [`RandomizerType::SevenBag`], but with two of each piece in every bag
+    FourteenBag,
+    /// Every piece is equally likely, regardless of what came before. The classic memoryless generator
+    Random,
+    /// Roll once, and roll again if the piece is the same as the last one, as in NES Tetris
+    NesReroll,
+    /// Roll up to `rolls` times until the piece isn't one of the last four, as in the Tetris The Grand Master series. TGM uses 4 rolls and TGM2 uses 6
+    TgmHistory { rolls: u32 },
+}
+
+impl RandomizerType {
+    /// Create a randomizer dealing the given pieces
+    pub fn build(self, pieces: Vec<BlockType>) -> Box<dyn Randomizer> {
+        match self {
+            Self::SevenBag => Box::new(Bag::new(pieces, 1)),
+            Self::FourteenBag => Box::new(Bag::new(pieces, 2)),
+            Self::Random => Box::new(Memoryless { pieces }),
+            Self::NesReroll => Box::new(NesReroll {
+                pieces,
+                last_piece: None,
+            }),
+            Self::TgmHistory { rolls } => Box::new(TgmHistory::new(pieces, rolls)),
+        }
+    }
+}
+
+/// Pick any one of the pieces. Samples a `u32` rather than a `usize`, so a seed picks the same pieces on 32 and 64 bit platforms
+fn random_piece(pieces: &[BlockType], rng: &mut dyn RngCore) -> BlockType {
+    pieces[rng.gen_range(0..pieces.len() as u32) as usize]
+}
+
+struct Bag {
+    pieces: Vec<BlockType>,
+    copies: usize,
+    /// The pieces left in the current bag
+    remaining: Vec<BlockType>,
+}
+
+impl Bag {
+    const fn new(pieces: Vec<BlockType>, copies: usize) -> Self {
+        Self {
+            pieces,
+            copies,
+            remaining: vec![],
+        }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> BlockType {
+        if self.remaining.is_empty() {
+            for _ in 0..self.copies {
+                self.remaining.extend(&self.pieces);
+            }
+            self.remaining.shuffle(rng);
+        }
+
+        self.remaining.pop().unwrap_or_else(|| unreachable!())
+    }
+}
+
+struct Memoryless {
+    pieces: Vec<BlockType>,
+}
+
+impl Randomizer for Memoryless {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> BlockType {
+        random_piece(&self.pieces, rng)
+    }
+}
+
+struct NesReroll {
+    pieces: Vec<BlockType>,
+    last_piece: Option<BlockType>,
+}
+
+impl Randomizer for NesReroll {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> BlockType {
+        // The first roll has one extra outcome which, like repeating the last piece, forces a reroll
+        let roll = rng.gen_range(0..=self.pieces.len() as u32) as usize;
+        let piece = match self.pieces.get(roll) {
+            Some(&piece) if Some(piece) != self.last_piece => piece,
+            _ => random_piece(&self.pieces, rng),
+        };
+
+        self.last_piece = Some(piece);
+        piece
+    }
+}
+
+struct TgmHistory {
+    pieces: Vec<BlockType>,
+    history: VecDeque<BlockType>,
+    rolls: u32,
+    is_first_piece: bool,
+}
+
+impl TgmHistory {
+    fn new(pieces: Vec<BlockType>, rolls: u32) -> Self {
+        Self {
+            pieces,
+            // Starting with S and Z in the history makes them unlikely to come early
+            history: VecDeque::from([BlockType::Z, BlockType::S, BlockType::S, BlockType::Z]),
+            rolls,
+            is_first_piece: true,
+        }
+    }
+}
+
+impl Randomizer for TgmHistory {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> BlockType {
+        let piece = if self.is_first_piece {
+            // The first piece is never an S, Z or O, which would force an overhang
+            self.is_first_piece = false;
+            let first_pieces: Vec<BlockType> = self
+                .pieces
+                .iter()
+                .copied()
+                .filter(|piece| !matches!(piece, BlockType::S | BlockType::Z | BlockType::O))
+                .collect();
+
+            first_pieces
+                .choose(rng)
+                .copied()
+                .unwrap_or_else(|| random_piece(&self.pieces, rng))
+        } else {
+            let mut piece = random_piece(&self.pieces, rng);
+            for _ in 1..self.rolls {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = random_piece(&self.pieces, rng);
+            }
+            piece
+        };
+
+        self.history.pop_front();
+        self.history.push_back(piece);
+        piece
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn rng(seed: u64) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(seed)
+    }
+
+    fn deal(randomizer: &mut dyn Randomizer, seed: u64, count: usize) -> Vec<BlockType> {
+        let mut rng = rng(seed);
+        (0..count)
+            .map(|_| randomizer.next_piece(&mut rng))
+            .collect()
+    }
+
+    fn assert_every_bag_is_full(randomizer: RandomizerType, copies: usize) {
+        let bag_size = BlockType::TETROMINOES.len() * copies;
+        let mut randomizer = randomizer.build(BlockType::TETROMINOES.to_vec());
+
+        for bag in deal(randomizer.as_mut(), 1, bag_size * 10).chunks(bag_size) {
+            for piece in BlockType::TETROMINOES {
+                assert_eq!(bag.iter().filter(|&&p| p == piece).count(), copies);
+            }
+        }
+    }
+
+    #[test]
+    fn seven_bag_deals_every_piece_once_per_bag() {
+        assert_every_bag_is_full(RandomizerType::SevenBag, 1);
+    }
+
+    #[test]
+    fn fourteen_bag_deals_every_piece_twice_per_bag() {
+        assert_every_bag_is_full(RandomizerType::FourteenBag, 2);
+    }
+
+    #[test]
+    fn seed_deals_the_same_pieces() {
+        use BlockType::{I, J, L, O, S, T, Z};
+
+        // Games shared by seed must keep dealing these exact pieces
+        for (randomizer, expected) in [
+            (RandomizerType::SevenBag, vec![O, L, I, T, J, S, Z, T, J, S]),
+            (RandomizerType::Random, vec![O, L, I, J, S, L, J, T, Z, O]),
+            (
+                RandomizerType::NesReroll,
+                vec![S, O, J, L, O, J, I, J, I, L],
+            ),
+            (
+                RandomizerType::TgmHistory { rolls: 4 },
+                vec![L, I, J, S, T, Z, O, J, I, O],
+            ),
+        ] {
+            let mut dealt = randomizer.build(BlockType::TETROMINOES.to_vec());
+            assert_eq!(
+                deal(dealt.as_mut(), 1, 10),
+                expected,
+                "{randomizer:?} dealt different pieces"
+            );
+        }
+    }
+
+    #[test]
+    fn random_pieces_are_the_same_on_every_platform() {
+        use BlockType::{I, J, L, O, S, T, Z};
+
+        let mut rng = rng(1);
+        let pieces: Vec<BlockType> = (0..10)
+            .map(|_| random_piece(&BlockType::TETROMINOES, &mut rng))
+            .collect();
+        assert_eq!(pieces, vec![O, L, I, J, S, L, J, T, Z, O]);
+    }
+
+    #[test]
+    fn nes_reroll_rarely_repeats() {
+        let mut randomizer = RandomizerType::NesReroll.build(BlockType::TETROMINOES.to_vec());
+        let pieces = deal(randomizer.as_mut(), 1, 7000);
+
+        // A repeat needs a reroll and then the same piece again, 1 in 56, rather than 1 in 7 without rerolling
+        let repeats = pieces.windows(2).filter(|pair| pair[0] == pair[1]).count();
+        assert!(repeats < 250, "{repeats} repeats");
+        for piece in BlockType::TETROMINOES {
+            assert!(pieces.contains(&piece));
+        }
+    }
+
+    #[test]
+    fn tgm_history_never_starts_with_s_z_or_o() {
+        for seed in 0..100 {
+            let mut randomizer =
+                RandomizerType::TgmHistory { rolls: 4 }.build(BlockType::TETROMINOES.to_vec());
+            let first_piece = deal(randomizer.as_mut(), seed, 1)[0];
+            assert!(
+                !matches!(first_piece, BlockType::S | BlockType::Z | BlockType::O),
+                "seed {seed} started with {first_piece:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn tgm_history_avoids_the_last_four_pieces() {
+        // With enough rolls, a piece from the history is never dealt
+        let mut randomizer =
+            RandomizerType::TgmHistory { rolls: 1000 }.build(BlockType::TETROMINOES.to_vec());
+        let mut history = VecDeque::from([BlockType::Z, BlockType::S, BlockType::S, BlockType::Z]);
+
+        for piece in deal(randomizer.as_mut(), 1, 1000) {
+            assert!(!history.contains(&piece), "{piece:?} is in {history:?}");
+            history.pop_front();
+            history.push_back(piece);
+        }
+    }
+}
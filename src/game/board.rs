@@ -0,0 +1,298 @@
+use std::rc::Rc;
+
+use gemini_engine::elements::{
+    view::{ColChar, Colour, ViewElement},
+    Pixel, PixelContainer, Rect, Vec2D,
+};
+
+use super::block_manager::{Block, BlockType, RotationSystem};
+
+/// The contents of a filled cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    /// Part of a placed block, with the block's shape
+    Block(BlockType),
+    Garbage,
+}
+
+impl Cell {
+    fn get_colour(self, rotation_system: &dyn RotationSystem) -> ColChar {
+        match self {
+            Self::Block(shape) => ColChar::SOLID.with_colour(rotation_system.colour(shape)),
+            Self::Garbage => ColChar::SOLID.with_colour(Colour::greyscale(128)),
+        }
+    }
+}
+
+/// The playfield as a fixed-size grid of cells. `(0, 0)` is the top-left cell of the board, and everything to the left, right or below the grid counts as a wall. Cells above the grid are always free
+///
+/// The top `hidden_rows` rows are the vanish zone, which sits above the visible field. Pieces spawn at the bottom of it
+#[derive(Debug, Clone)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    hidden_rows: usize,
+    cells: Vec<Option<Cell>>,
+    /// One bit per filled cell for each row, used to find full lines without scanning every cell
+    row_masks: Vec<u64>,
+    /// Used to colour placed blocks by their shape
+    rotation_system: Rc<dyn RotationSystem>,
+}
+
+impl Board {
+    pub fn new(
+        width: usize,
+        visible_height: usize,
+        hidden_rows: usize,
+        rotation_system: Rc<dyn RotationSystem>,
+    ) -> Self {
+        assert!(
+            (4..=64).contains(&width),
+            "board width should be between 4 and 64"
+        );
+
+        let height = visible_height + hidden_rows;
+        Self {
+            width,
+            height,
+            hidden_rows,
+            cells: vec![None; width * height],
+            row_masks: vec![0; height],
+            rotation_system,
+        }
+    }
+
+    /// Build a board with no vanish zone from rows of text, top to bottom, where `#` is a filled cell
+    #[cfg(test)]
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let mut board = Self::new(
+            rows[0].len(),
+            rows.len(),
+            0,
+            Rc::new(super::block_manager::PieceSet::srs()),
+        );
+        for (y, row) in rows.iter().enumerate() {
+            for (x, cell) in row.chars().enumerate() {
+                if cell == '#' {
+                    board.cells[y * board.width + x] = Some(Cell::Garbage);
+                    board.row_masks[y] |= 1 << x;
+                }
+            }
+        }
+
+        board
+    }
+
+    /// Where new pieces appear: the centre column, rounded to the left, in the bottom row of the vanish zone
+    pub const fn spawn_position(&self) -> Vec2D {
+        Vec2D::new((self.width as isize - 1) / 2, self.hidden_rows as isize - 1)
+    }
+
+    /// Returns true if every cell of the block is inside the vanish zone
+    pub fn is_above_visible_field(&self, block: &Block) -> bool {
+        block
+            .active_points()
+            .iter()
+            .all(|p| p.y < self.hidden_rows as isize)
+    }
+
+    const fn full_row_mask(&self) -> u64 {
+        u64::MAX >> (64 - self.width)
+    }
+
+    fn index(&self, pos: Vec2D) -> Option<usize> {
+        if (0..self.width as isize).contains(&pos.x) && (0..self.height as isize).contains(&pos.y) {
+            Some(pos.y as usize * self.width + pos.x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the contents of the cell at the given position, or `None` if it is empty or outside the board
+    pub fn get(&self, pos: Vec2D) -> Option<Cell> {
+        self.index(pos).and_then(|i| self.cells[i])
+    }
+
+    /// Returns true if the position is filled or is part of the walls or floor
+    pub fn is_occupied(&self, pos: Vec2D) -> bool {
+        if pos.x < 0 || pos.x >= self.width as isize || pos.y >= self.height as isize {
+            return true;
+        }
+
+        self.get(pos).is_some()
+    }
+
+    /// Returns true if the block would overlap with the board after being moved by `offset`
+    pub fn will_overlap(&self, block: &Block, offset: Vec2D) -> bool {
+        block
+            .active_points()
+            .into_iter()
+            .any(|p| self.is_occupied(p + offset))
+    }
+
+    pub fn overlaps(&self, block: &Block) -> bool {
+        self.will_overlap(block, Vec2D::ZERO)
+    }
+
+    /// Lock a block's cells into the board. Any cells outside the board are discarded
+    pub fn place(&mut self, block: &Block) {
+        for point in block.active_points() {
+            if let Some(i) = self.index(point) {
+                self.cells[i] = Some(Cell::Block(block.shape));
+                self.row_masks[point.y as usize] |= 1 << point.x;
+            }
+        }
+    }
+
+    /// Push everything up and fill the bottom `lines` rows with garbage, leaving a hole at `hole_x`
+    ///
+    /// Returns true if any filled cells were pushed off the top of the board, indicating a lost game
+    pub fn add_garbage(&mut self, lines: usize, hole_x: usize) -> bool {
+        assert!(
+            hole_x < self.width,
+            "garbage hole should be inside the board"
+        );
+        let lines = lines.min(self.height);
+        let pushed_out = self.row_masks[..lines].iter().any(|mask| *mask != 0);
+
+        self.row_masks.copy_within(lines.., 0);
+        self.cells.copy_within(lines * self.width.., 0);
+
+        let garbage_mask = self.full_row_mask() & !(1 << hole_x);
+        for y in self.height - lines..self.height {
+            self.row_masks[y] = garbage_mask;
+            for x in 0..self.width {
+                self.cells[y * self.width + x] = (x != hole_x).then_some(Cell::Garbage);
+            }
+        }
+
+        pushed_out
+    }
+
+    /// Remove all filled lines, shifting everything above them down, and return the number of lines removed
+    pub fn clear_filled_lines(&mut self) -> isize {
+        let full_row_mask = self.full_row_mask();
+        let mut cleared_lines = 0;
+
+        // Walk from the bottom up, copying every unfilled row down into its new position
+        let mut target_y = self.height;
+        for y in (0..self.height).rev() {
+            if self.row_masks[y] == full_row_mask {
+                cleared_lines += 1;
+                continue;
+            }
+
+            target_y -= 1;
+            if target_y != y {
+                self.row_masks[target_y] = self.row_masks[y];
+                self.cells
+                    .copy_within(y * self.width..(y + 1) * self.width, target_y * self.width);
+            }
+        }
+
+        self.row_masks[..target_y].fill(0);
+        self.cells[..target_y * self.width].fill(None);
+
+        cleared_lines
+    }
+
+    /// Returns true if there are no filled cells anywhere on the board
+    pub fn is_empty(&self) -> bool {
+        self.row_masks.iter().all(|mask| *mask == 0)
+    }
+
+    /// Generate the walls and floor surrounding the visible field
+    pub fn borders(&self) -> PixelContainer {
+        let (width, height) = (self.width as isize, self.height as isize);
+        let top = self.hidden_rows as isize;
+
+        let mut borders = PixelContainer::new();
+        borders.blit(&Rect::new(
+            // Left wall
+            Vec2D::new(-1, top),
+            Vec2D::new(1, height - top + 1),
+            ColChar::SOLID,
+        ));
+        borders.blit(&Rect::new(
+            // Right wall
+            Vec2D::new(width, top),
+            Vec2D::new(1, height - top + 1),
+            ColChar::SOLID,
+        ));
+        borders.blit(&Rect::new(
+            // Floor
+            Vec2D::new(0, height),
+            Vec2D::new(width, 1),
+            ColChar::SOLID,
+        ));
+
+        borders
+    }
+}
+
+impl ViewElement for Board {
+    fn active_pixels(&self) -> Vec<Pixel> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cell)| {
+                cell.map(|cell| {
+                    Pixel::new(
+                        Vec2D::new((i % self.width) as isize, (i / self.width) as isize),
+                        cell.get_colour(self.rotation_system.as_ref()),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::block_manager::PieceSet;
+
+    #[test]
+    fn placed_cells_remember_their_shape() {
+        let mut board = Board::from_rows(&["....", "...."]);
+        let rotation_system: Rc<dyn RotationSystem> = Rc::new(PieceSet::srs());
+        let mut block = Block::new(BlockType::T, &rotation_system);
+        block.pos = Vec2D::new(1, 1);
+
+        board.place(&block);
+        assert_eq!(board.get(Vec2D::new(1, 1)), Some(Cell::Block(BlockType::T)));
+    }
+
+    #[test]
+    fn garbage_rises_with_a_hole() {
+        #[rustfmt::skip]
+        let mut board = Board::from_rows(&[
+            "....",
+            "....",
+            "##..",
+        ]);
+
+        assert!(!board.add_garbage(1, 3));
+        assert!(board.is_occupied(Vec2D::new(0, 1)));
+        assert!(!board.is_occupied(Vec2D::new(2, 1)));
+        assert!(board.is_occupied(Vec2D::new(2, 2)));
+        assert!(!board.is_occupied(Vec2D::new(3, 2)));
+    }
+
+    #[test]
+    fn garbage_pushing_cells_off_the_top_tops_out() {
+        #[rustfmt::skip]
+        let mut board = Board::from_rows(&[
+            "#...",
+            "....",
+        ]);
+
+        assert!(board.add_garbage(1, 0));
+    }
+
+    #[test]
+    #[should_panic = "garbage hole should be inside the board"]
+    fn garbage_hole_outside_the_board_panics() {
+        Board::from_rows(&["....", "...."]).add_garbage(1, 4);
+    }
+}
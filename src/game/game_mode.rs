@@ -0,0 +1,74 @@
+use super::{game_state::GameOverReason, FPS};
+
+/// What the player is trying to do, and when the game ends other than by topping out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    /// Keep playing until topping out
+    Endless,
+    /// Clear `lines` lines, levelling up along the way
+    Marathon { lines: usize },
+    /// Clear `lines` lines as fast as possible
+    Sprint { lines: usize },
+    /// Score as much as possible before `frames` frames are up
+    Ultra { frames: u64 },
+}
+
+impl GameMode {
+    /// Marathon to 150 lines, as in the guideline
+    pub const MARATHON: Self = Self::Marathon { lines: 150 };
+    /// Sprint to 40 lines
+    pub const SPRINT: Self = Self::Sprint { lines: 40 };
+    /// Three minutes of Ultra, as in the guideline
+    pub const ULTRA: Self = Self::Ultra {
+        frames: 180 * FPS as u64,
+    };
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Endless => "Endless",
+            Self::Marathon { .. } => "Marathon",
+            Self::Sprint { .. } => "Sprint",
+            Self::Ultra { .. } => "Ultra",
+        }
+    }
+
+    /// Why the game should end, if it has reached its goal after clearing `lines` lines in `frames` frames
+    pub const fn check_goal(self, lines: usize, frames: u64) -> Option<GameOverReason> {
+        match self {
+            Self::Marathon { lines: goal } | Self::Sprint { lines: goal } if lines >= goal => {
+                Some(GameOverReason::GoalReached)
+            }
+            Self::Ultra { frames: limit } if frames >= limit => Some(GameOverReason::TimeUp),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_goals_end_the_game_once_reached() {
+        for mode in [
+            GameMode::Marathon { lines: 40 },
+            GameMode::Sprint { lines: 40 },
+        ] {
+            assert_eq!(mode.check_goal(39, 100_000), None);
+            assert_eq!(mode.check_goal(40, 0), Some(GameOverReason::GoalReached));
+            assert_eq!(mode.check_goal(43, 0), Some(GameOverReason::GoalReached));
+        }
+    }
+
+    #[test]
+    fn ultra_ends_when_time_is_up() {
+        let mode = GameMode::Ultra { frames: 600 };
+        assert_eq!(mode.check_goal(1000, 599), None);
+        assert_eq!(mode.check_goal(0, 600), Some(GameOverReason::TimeUp));
+    }
+
+    #[test]
+    fn endless_never_ends() {
+        assert_eq!(GameMode::Endless.check_goal(usize::MAX, u64::MAX), None);
+    }
+}
@@ -0,0 +1,839 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    rc::Rc,
+};
+
+use gemini_engine::elements::Vec2D;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    auto_shift::{AutoShift, AutoShiftMove},
+    block_manager::{BlockManager, Rotation, Spin},
+    board::Board,
+    game_mode::GameMode,
+    gravity::{GravityCurve, G, MAX_GRAVITY},
+    scoring::{Lock, ScoringSystem},
+    settings::{GameSettings, SoftDrop},
+};
+
+/// An action the player can ask the game to perform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    ShiftLeft,
+    ShiftRight,
+    RotateClockwise,
+    RotateAnticlockwise,
+    Rotate180,
+    SoftDrop,
+    HardDrop,
+    Hold,
+}
+
+impl Action {
+    /// The way this action turns the active block, if it is a rotation
+    const fn rotation(self) -> Option<Rotation> {
+        match self {
+            Self::RotateClockwise => Some(Rotation::Clockwise),
+            Self::RotateAnticlockwise => Some(Rotation::Anticlockwise),
+            Self::Rotate180 => Some(Rotation::Half),
+            _ => None,
+        }
+    }
+}
+
+/// The player's input for a single tick
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GameInput {
+    /// Actions whose keys were pressed this tick, in the order they were pressed
+    pub pressed: Vec<Action>,
+    /// Actions whose keys are currently held down, including any pressed this tick
+    pub held: HashSet<Action>,
+    /// Whether the keys in `held` are known to be held because the terminal reports key releases. Otherwise a key pressed a moment ago may still count as held, so only presses buffered while waiting for a piece are used for IRS and IHS
+    pub reports_releases: bool,
+}
+
+impl GameInput {
+    pub fn press(&mut self, action: Action) {
+        self.pressed.push(action);
+        self.held.insert(action);
+    }
+
+    pub fn is_held(&self, action: Action) -> bool {
+        self.held.contains(&action)
+    }
+}
+
+/// Something that happened during a tick which a frontend may want to react to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    /// The active piece was hard dropped by the given number of rows, awarding the given score. It locks straight away
+    HardDrop { rows: isize, score: isize },
+    /// The active piece was locked into the board, clearing the given number of lines
+    PieceLocked { cleared_lines: isize },
+    /// A scoring event, such as a line clear or T-spin, along with the score it awarded
+    Alert { text: String, score: isize },
+    /// Enough lines were cleared to move up to the given level
+    LevelUp(usize),
+    /// The game has ended, by topping out or by the mode coming to an end
+    GameOver(GameOverReason),
+}
+
+/// Why the game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOverReason {
+    TopOut(TopOutReason),
+    /// The mode's line goal was reached
+    GoalReached,
+    /// The mode's time limit ran out
+    TimeUp,
+}
+
+impl Display for GameOverReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TopOut(reason) => reason.fmt(f),
+            Self::GoalReached => f.write_str("Goal reached"),
+            Self::TimeUp => f.write_str("Time up"),
+        }
+    }
+}
+
+/// The way in which the player topped out, named as in the guideline
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopOutReason {
+    /// A new piece spawned overlapping the stack
+    BlockOut,
+    /// A piece locked entirely inside the vanish zone
+    LockOut,
+    /// Garbage pushed the stack off the top of the board
+    GarbageOut,
+}
+
+impl Display for TopOutReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BlockOut => "Block out",
+            Self::LockOut => "Lock out",
+            Self::GarbageOut => "Top out",
+        })
+    }
+}
+
+/// Counts of notable things that happened over the game
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    /// Line clears that left the board completely empty
+    pub perfect_clears: usize,
+    /// How many ticks the game has lasted, including delays between pieces
+    pub frames: u64,
+}
+
+/// What the game is doing, from one piece entering to the next
+enum Phase {
+    /// The active block is in play
+    Active,
+    /// The cleared lines are still being shown on `board`, before they disappear
+    LineClear { frames_left: u32, board: Board },
+    /// Waiting for the next piece to enter (ARE)
+    Entry { frames_left: u32 },
+}
+
+/// The rules of the game, free of any rendering or terminal I/O. Advance it with [`GameState::tick`]
+#[allow(clippy::struct_excessive_bools)]
+pub struct GameState {
+    pub block_manager: BlockManager,
+    pub board: Board,
+    auto_shift: AutoShift,
+    pub score: isize,
+    pub lines: usize,
+    pub level: usize,
+    pub game_over: Option<GameOverReason>,
+    /// How many locks in a row have cleared lines, minus one. `-1` once a lock clears nothing
+    pub combo: isize,
+    /// Whether the last line clear was a difficult one, so the next difficult clear is a back-to-back
+    pub back_to_back: bool,
+    pub stats: Statistics,
+    scoring: Box<dyn ScoringSystem>,
+    /// The seed of `rng`, which can be used to replay the game with the same pieces
+    pub seed: u64,
+    /// Every random choice in the game comes from here, so games with the same seed play out the same. Unlike `StdRng`, `ChaCha8Rng` is guaranteed to give the same numbers on every platform and version
+    rng: ChaCha8Rng,
+    /// Garbage waiting to be added to the bottom of the board, as `(lines, hole column)` pairs
+    garbage_queue: Vec<(usize, usize)>,
+    /// How far the active block has fallen towards the next row, in the units of [`G`]
+    gravity_progress: u32,
+    phase: Phase,
+    /// Rotations and holds pressed while waiting for the next piece, to be used as initial actions when it enters
+    buffered_actions: Vec<Action>,
+    // Constants
+    mode: GameMode,
+    start_level: usize,
+    lines_per_level: usize,
+    gravity_curve: GravityCurve,
+    soft_drop: SoftDrop,
+    allow_180_rotation: bool,
+    all_spin: bool,
+    initial_actions: bool,
+    are: u32,
+    line_clear_delay: u32,
+}
+
+impl GameState {
+    pub fn new(settings: &GameSettings) -> Self {
+        let rotation_system = settings.rotation_system.build();
+        let board = Board::new(
+            settings.board_width,
+            settings.board_height,
+            settings.hidden_rows,
+            Rc::clone(&rotation_system),
+        );
+
+        let seed = settings
+            .seed
+            .unwrap_or_else(|| rand::random::<u32>().into());
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        Self {
+            block_manager: BlockManager::new(
+                settings.block_place_cooldown,
+                settings.lock_reset,
+                settings.piece_preview_count,
+                board.spawn_position(),
+                rotation_system,
+                settings.randomizer,
+                &mut rng,
+            ),
+            board,
+            auto_shift: AutoShift::new(settings.das, settings.arr, settings.das_cut_delay),
+            score: 0,
+            lines: 0,
+            level: settings.start_level,
+            game_over: None,
+            combo: -1,
+            back_to_back: false,
+            stats: Statistics::default(),
+            scoring: settings.scoring.build(),
+            seed,
+            rng,
+            garbage_queue: vec![],
+            gravity_progress: 0,
+            phase: Phase::Active,
+            buffered_actions: vec![],
+            mode: settings.mode,
+            start_level: settings.start_level,
+            lines_per_level: settings.lines_per_level,
+            gravity_curve: settings.gravity_curve.clone(),
+            soft_drop: settings.soft_drop,
+            allow_180_rotation: settings.allow_180_rotation,
+            all_spin: settings.all_spin,
+            initial_actions: settings.initial_actions,
+            are: settings.are,
+            line_clear_delay: settings.line_clear_delay,
+        }
+    }
+
+    /// Whether there is an active block in play, rather than the game waiting for the next piece
+    pub const fn has_active_block(&self) -> bool {
+        matches!(self.phase, Phase::Active)
+    }
+
+    /// The board as it should be shown, which still has the cleared lines in during the line clear delay
+    pub const fn displayed_board(&self) -> &Board {
+        match &self.phase {
+            Phase::LineClear { board, .. } => board,
+            Phase::Active | Phase::Entry { .. } => &self.board,
+        }
+    }
+
+    pub const fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    /// The current gravity, in the units of [`G`]
+    pub fn gravity(&self) -> u32 {
+        self.gravity_curve.gravity(self.level)
+    }
+
+    /// Add to the cleared line count, moving up a level each time another `lines_per_level` lines are cleared
+    fn add_cleared_lines(&mut self, cleared_lines: usize, events: &mut Vec<GameEvent>) {
+        self.lines += cleared_lines;
+
+        let level = self.start_level + self.lines / self.lines_per_level;
+        if level > self.level {
+            self.level = level;
+            events.push(GameEvent::LevelUp(level));
+        }
+    }
+
+    /// Move the active block down by however many whole rows the given amount of gravity adds up to. Returns the number of rows moved
+    fn apply_gravity(&mut self, gravity: u32) -> isize {
+        let mut rows_moved = 0;
+
+        self.gravity_progress += gravity;
+        while self.gravity_progress >= G {
+            self.gravity_progress -= G;
+            if !self
+                .block_manager
+                .try_move_block(&self.board, Vec2D::new(0, 1))
+            {
+                self.gravity_progress = 0;
+                break;
+            }
+            rows_moved += 1;
+        }
+
+        rows_moved
+    }
+
+    /// Queue garbage to rise from the bottom of the board the next time a piece locks without clearing any lines
+    #[allow(dead_code)] // No mode sends garbage yet, but bots and versus frontends can
+    pub fn queue_garbage(&mut self, lines: usize, hole_x: usize) {
+        self.garbage_queue.push((lines, hole_x));
+    }
+
+    fn end_game(&mut self, reason: GameOverReason, events: &mut Vec<GameEvent>) {
+        self.game_over = Some(reason);
+        events.push(GameEvent::GameOver(reason));
+    }
+
+    /// Move the active block down as far as it will go without locking it. Returns the number of rows moved
+    fn drop_to_floor(&mut self) -> isize {
+        let mut rows_moved = 0;
+        while self
+            .block_manager
+            .try_move_block(&self.board, Vec2D::new(0, 1))
+        {
+            rows_moved += 1;
+        }
+        self.gravity_progress = 0;
+
+        rows_moved
+    }
+
+    /// Bring in the next piece. If initial actions are enabled, holding the hold key swaps it straight into hold (IHS) and holding a rotate key spawns it rotated (IRS)
+    fn spawn_next_block(&mut self, input: &GameInput, events: &mut Vec<GameEvent>) {
+        self.phase = Phase::Active;
+        self.block_manager.generate_new_block(&mut self.rng);
+
+        let buffered_actions = std::mem::take(&mut self.buffered_actions);
+        if self.initial_actions {
+            // Keys pressed this tick were already used by the piece that just locked, unless they were pressed while waiting for this one
+            let is_held = |action| {
+                buffered_actions.contains(&action)
+                    || (input.reports_releases
+                        && input.is_held(action)
+                        && !input.pressed.contains(&action))
+            };
+
+            if is_held(Action::Hold) {
+                self.block_manager.hold(&mut self.rng);
+            }
+
+            let rotation = [
+                Action::RotateClockwise,
+                Action::RotateAnticlockwise,
+                Action::Rotate180,
+            ]
+            .into_iter()
+            .filter(|&action| action != Action::Rotate180 || self.allow_180_rotation)
+            .find(|&action| is_held(action))
+            .and_then(Action::rotation);
+            if let Some(rotation) = rotation {
+                self.block_manager.initial_rotate(&self.board, rotation);
+            }
+        }
+
+        self.on_block_spawned(events);
+    }
+
+    /// Call whenever a new active block appears. Ends the game if it has nowhere to go, and otherwise drops it by the current gravity's whole rows, so at 20G it appears already on the stack
+    fn on_block_spawned(&mut self, events: &mut Vec<GameEvent>) {
+        if self.board.overlaps(&self.block_manager.block) {
+            self.end_game(GameOverReason::TopOut(TopOutReason::BlockOut), events);
+            return;
+        }
+
+        self.gravity_progress = 0;
+        self.apply_gravity(self.gravity() / G * G);
+    }
+
+    /// Award points for a lock that cleared `cleared_lines` lines on the given level, possibly with a spin or leaving the board empty, keeping track of combos and back-to-backs
+    fn score_clear(
+        &mut self,
+        cleared_lines: isize,
+        level: usize,
+        spin: Option<Spin>,
+        is_perfect_clear: bool,
+        events: &mut Vec<GameEvent>,
+    ) {
+        // Tetrises and spins are difficult clears, and a line clear that isn't difficult breaks the back-to-back chain
+        let is_difficult = cleared_lines >= 4 || spin.is_some();
+        let is_back_to_back = cleared_lines > 0 && is_difficult && self.back_to_back;
+        if cleared_lines > 0 {
+            self.back_to_back = is_difficult;
+            self.combo += 1;
+        } else {
+            self.combo = -1;
+        }
+
+        if is_perfect_clear {
+            self.stats.perfect_clears += 1;
+        }
+
+        for (score, text) in self.scoring.score_lock(&Lock {
+            cleared_lines,
+            spin,
+            is_perfect_clear,
+            is_back_to_back,
+            combo: self.combo,
+            level,
+        }) {
+            self.score += score;
+            events.push(GameEvent::Alert { text, score });
+        }
+    }
+
+    /// Lock the active block where it is, clearing lines, scoring the lock and bringing in the next piece
+    fn lock_block(&mut self, input: &GameInput, events: &mut Vec<GameEvent>) {
+        let pre_clear_board = self.board.clone();
+        self.block_manager.reset();
+
+        if self.board.is_above_visible_field(&self.block_manager.block) {
+            self.end_game(GameOverReason::TopOut(TopOutReason::LockOut), events);
+            return;
+        }
+
+        self.board.place(&self.block_manager.block);
+        let board_with_cleared_lines = (self.line_clear_delay > 0).then(|| self.board.clone());
+        let cleared_lines = self.board.clear_filled_lines();
+        events.push(GameEvent::PieceLocked { cleared_lines });
+        let level = self.level;
+        self.add_cleared_lines(cleared_lines as usize, events);
+
+        let spin = self
+            .block_manager
+            .check_for_spin(&pre_clear_board, self.all_spin);
+        let is_perfect_clear = cleared_lines > 0 && self.board.is_empty();
+        self.score_clear(cleared_lines, level, spin, is_perfect_clear, events);
+
+        if let Some(reason) = self.mode.check_goal(self.lines, self.stats.frames) {
+            self.end_game(reason, events);
+            return;
+        }
+
+        if cleared_lines == 0 {
+            for (lines, hole_x) in std::mem::take(&mut self.garbage_queue) {
+                if self.board.add_garbage(lines, hole_x) {
+                    self.end_game(GameOverReason::TopOut(TopOutReason::GarbageOut), events);
+                    return;
+                }
+            }
+        }
+
+        match board_with_cleared_lines {
+            Some(board) if cleared_lines > 0 => {
+                self.phase = Phase::LineClear {
+                    frames_left: self.line_clear_delay,
+                    board,
+                };
+            }
+            _ => self.start_entry_delay(input, events),
+        }
+    }
+
+    /// Wait for ARE before bringing in the next piece, or bring it in straight away if there is no ARE
+    fn start_entry_delay(&mut self, input: &GameInput, events: &mut Vec<GameEvent>) {
+        if self.are > 0 {
+            self.phase = Phase::Entry {
+                frames_left: self.are,
+            };
+        } else {
+            self.spawn_next_block(input, events);
+        }
+    }
+
+    /// Save presses made while there is no active block: shift keys charge DAS, and rotations and holds are kept for IRS and IHS
+    fn buffer_presses(&mut self, pressed: &[Action]) {
+        for &action in pressed {
+            match action {
+                Action::ShiftLeft => self.auto_shift.press(-1),
+                Action::ShiftRight => self.auto_shift.press(1),
+                Action::RotateClockwise
+                | Action::RotateAnticlockwise
+                | Action::Rotate180
+                | Action::Hold => self.buffered_actions.push(action),
+                Action::SoftDrop | Action::HardDrop => (),
+            }
+        }
+    }
+
+    /// Count down the line clear delay or ARE. Gravity is suspended, but shift keys still charge DAS and rotations and holds are saved for IRS and IHS
+    fn tick_delay(&mut self, input: &GameInput, events: &mut Vec<GameEvent>) {
+        self.buffer_presses(&input.pressed);
+        // There is no block to move yet
+        self.auto_shift.tick(
+            input.is_held(Action::ShiftLeft),
+            input.is_held(Action::ShiftRight),
+        );
+
+        let frames_left = match &mut self.phase {
+            Phase::LineClear { frames_left, .. } | Phase::Entry { frames_left } => {
+                *frames_left -= 1;
+                *frames_left
+            }
+            Phase::Active => return,
+        };
+        if frames_left == 0 {
+            if matches!(self.phase, Phase::LineClear { .. }) {
+                self.start_entry_delay(input, events);
+            } else {
+                self.spawn_next_block(input, events);
+            }
+        }
+    }
+
+    /// Drop the active block straight to the floor and lock it, scoring the rows it fell
+    fn hard_drop(&mut self, input: &GameInput, events: &mut Vec<GameEvent>) {
+        let rows = self.drop_to_floor();
+        let score = self.scoring.score_drop(rows, true);
+        self.score += score;
+        events.push(GameEvent::HardDrop { rows, score });
+
+        self.lock_block(input, events);
+    }
+
+    fn rotate(&mut self, rotation: Rotation) {
+        self.auto_shift.cut();
+        self.block_manager.try_rotate_block(&self.board, rotation);
+    }
+
+    /// Advance the game by a single frame, returning everything that happened during it
+    ///
+    /// Pressed actions are applied in order. A hard drop locks the active block straight away. Any presses after it are buffered like during the entry delay, or with no entry delay act on the next piece, which doesn't fall until the following tick
+    pub fn tick(&mut self, input: &GameInput) -> Vec<GameEvent> {
+        let mut events = vec![];
+        if self.game_over.is_some() {
+            return events;
+        }
+        self.stats.frames += 1;
+
+        if let Some(reason) = self.mode.check_goal(self.lines, self.stats.frames) {
+            self.end_game(reason, &mut events);
+            return events;
+        }
+
+        if !self.has_active_block() {
+            self.tick_delay(input, &mut events);
+            return events;
+        }
+
+        // Whether a hard drop brought in a new piece this tick
+        let mut has_spawned = false;
+        for (i, action) in input.pressed.iter().enumerate() {
+            match action {
+                Action::ShiftLeft => {
+                    self.auto_shift.press(-1);
+                    self.block_manager
+                        .try_move_block(&self.board, Vec2D::new(-1, 0));
+                }
+                Action::ShiftRight => {
+                    self.auto_shift.press(1);
+                    self.block_manager
+                        .try_move_block(&self.board, Vec2D::new(1, 0));
+                }
+                Action::RotateAnticlockwise => self.rotate(Rotation::Anticlockwise),
+                Action::RotateClockwise => self.rotate(Rotation::Clockwise),
+                Action::Rotate180 => {
+                    if self.allow_180_rotation {
+                        self.rotate(Rotation::Half);
+                    }
+                }
+                Action::SoftDrop => (),
+                Action::HardDrop => {
+                    self.hard_drop(input, &mut events);
+                    if self.game_over.is_some() {
+                        return events;
+                    }
+                    // The locked block is gone, so the rest of the presses wait for the next piece
+                    if !self.has_active_block() {
+                        self.buffer_presses(&input.pressed[i + 1..]);
+                        self.auto_shift.tick(
+                            input.is_held(Action::ShiftLeft),
+                            input.is_held(Action::ShiftRight),
+                        );
+                        return events;
+                    }
+                    // With no entry delay the next piece is already in, and the rest of the presses act on it
+                    has_spawned = true;
+                }
+                Action::Hold => {
+                    if self.block_manager.hold(&mut self.rng) {
+                        self.on_block_spawned(&mut events);
+                        if self.game_over.is_some() {
+                            return events;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Keep moving the block while a shift key is held
+        match self.auto_shift.tick(
+            input.is_held(Action::ShiftLeft),
+            input.is_held(Action::ShiftRight),
+        ) {
+            Some(AutoShiftMove::Step(direction)) => {
+                self.block_manager
+                    .try_move_block(&self.board, Vec2D::new(direction, 0));
+            }
+            Some(AutoShiftMove::ToWall(direction)) => {
+                self.block_manager.shift_to_wall(&self.board, direction);
+            }
+            None => (),
+        }
+
+        // Place the ghost block directly beneath the active block
+        self.block_manager.generate_ghost_block(&self.board);
+
+        // A piece that only just spawned has already fallen by its spawn gravity, so it starts falling and counting down its lock delay next tick
+        if has_spawned {
+            return events;
+        }
+
+        // If the active block is on the floor...
+        if self
+            .board
+            .will_overlap(&self.block_manager.block, Vec2D::new(0, 1))
+        {
+            // If the block's way down is blocked...
+            if self.block_manager.tick_lock_delay() {
+                self.lock_block(input, &mut events);
+                if self.game_over.is_some() {
+                    return events;
+                }
+            }
+        } else if input.is_held(Action::SoftDrop) {
+            // move down and score every row soft dropped
+            let rows = match self.soft_drop {
+                SoftDrop::Factor(factor) => {
+                    self.apply_gravity(self.gravity().saturating_mul(factor).min(MAX_GRAVITY))
+                }
+                SoftDrop::Instant => self.drop_to_floor(),
+            };
+            self.score += self.scoring.score_drop(rows, false);
+        } else {
+            self.apply_gravity(self.gravity());
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::*;
+    use crate::game::{
+        key_tracker::KeyTracker, LockReset, RandomizerType, RotationSystemType, ScoringSystemType,
+    };
+
+    fn test_settings() -> GameSettings {
+        GameSettings {
+            mode: GameMode::Endless,
+            board_width: 10,
+            board_height: 20,
+            hidden_rows: 20,
+            block_place_cooldown: 30,
+            lock_reset: LockReset::GUIDELINE,
+            start_level: 1,
+            lines_per_level: 10,
+            gravity_curve: GravityCurve::Guideline,
+            das: 10,
+            arr: 2,
+            das_cut_delay: 0,
+            soft_drop: SoftDrop::Factor(20),
+            piece_preview_count: 3,
+            randomizer: RandomizerType::SevenBag,
+            seed: Some(1),
+            rotation_system: RotationSystemType::Srs,
+            allow_180_rotation: true,
+            all_spin: false,
+            scoring: ScoringSystemType::Guideline,
+            initial_actions: true,
+            are: 0,
+            line_clear_delay: 0,
+        }
+    }
+
+    fn press(actions: &[Action]) -> GameInput {
+        let mut input = GameInput::default();
+        for &action in actions {
+            input.press(action);
+        }
+        input
+    }
+
+    fn count_locks(events: &[GameEvent]) -> usize {
+        events
+            .iter()
+            .filter(|event| matches!(event, GameEvent::PieceLocked { .. }))
+            .count()
+    }
+
+    #[test]
+    fn hard_drop_locks_in_the_same_tick() {
+        let mut state = GameState::new(&test_settings());
+        state.tick(&GameInput::default());
+        let rows = state.block_manager.ghost_block.pos.y - state.block_manager.block.pos.y;
+        let dropped = state.block_manager.block.shape;
+
+        let events = state.tick(&press(&[Action::HardDrop]));
+
+        assert_eq!(
+            events[0],
+            GameEvent::HardDrop {
+                rows,
+                score: rows * 2
+            }
+        );
+        assert_eq!(count_locks(&events), 1);
+        assert_eq!(state.score, rows * 2);
+        assert!(state.has_active_block());
+        assert_ne!(state.block_manager.block.shape, dropped);
+    }
+
+    #[test]
+    fn presses_after_a_hard_drop_act_on_the_next_piece() {
+        let mut state = GameState::new(&test_settings());
+        let dropped = state.block_manager.block.shape;
+
+        state.tick(&press(&[Action::HardDrop, Action::Hold]));
+
+        let held = state.block_manager.held_piece;
+        assert!(held.is_some());
+        assert_ne!(held, Some(dropped));
+    }
+
+    #[test]
+    fn spawned_piece_waits_a_tick_before_locking() {
+        // At 20G the next piece lands on the stack as it spawns, and would lock straight away with a one frame lock delay
+        let mut state = GameState::new(&GameSettings {
+            gravity_curve: GravityCurve::Table(vec![MAX_GRAVITY]),
+            block_place_cooldown: 1,
+            ..test_settings()
+        });
+
+        let events = state.tick(&press(&[Action::HardDrop]));
+        assert_eq!(count_locks(&events), 1);
+
+        let events = state.tick(&GameInput::default());
+        assert_eq!(count_locks(&events), 1);
+    }
+
+    #[test]
+    fn hold_after_a_hard_drop_waits_for_the_next_piece() {
+        let mut state = GameState::new(&GameSettings {
+            are: 10,
+            ..test_settings()
+        });
+        let dropped = state.block_manager.block.shape;
+
+        state.tick(&press(&[Action::HardDrop, Action::Hold]));
+        assert_eq!(state.block_manager.held_piece, None);
+
+        while !state.has_active_block() {
+            state.tick(&GameInput::default());
+        }
+        // The hold was used as IHS on the next piece, rather than holding the dropped one again
+        let held = state.block_manager.held_piece;
+        assert!(held.is_some());
+        assert_ne!(held, Some(dropped));
+    }
+
+    #[test]
+    fn hard_drop_on_the_last_lock_delay_frame_locks_once() {
+        let settings = GameSettings {
+            are: 10,
+            ..test_settings()
+        };
+        let mut state = GameState::new(&settings);
+        state.drop_to_floor();
+        for _ in 1..settings.block_place_cooldown {
+            state.tick(&GameInput::default());
+        }
+
+        let events = state.tick(&press(&[Action::HardDrop]));
+        assert_eq!(count_locks(&events), 1);
+    }
+
+    #[test]
+    fn tapped_shift_moves_one_cell_without_key_releases() {
+        let mut state = GameState::new(&test_settings());
+        let mut keys = KeyTracker::new(false);
+        let start_x = state.block_manager.block.pos.x;
+        let start = Instant::now();
+
+        // Run for a second after a single tap, long enough for DAS to charge if the key counted as held
+        for frame in 0..60 {
+            let now = start + Duration::from_millis(frame * 1000 / 60);
+            keys.release_stale_keys_at(now);
+
+            let mut input = GameInput::default();
+            let tap = KeyEvent::new(KeyCode::Left, KeyModifiers::NONE);
+            if frame == 0 && keys.handle_at(&tap, now) {
+                input.press(Action::ShiftLeft);
+            }
+            if keys.held_keys().any(|code| code == KeyCode::Left) {
+                input.held.insert(Action::ShiftLeft);
+            }
+            state.tick(&input);
+        }
+
+        assert_eq!(state.block_manager.block.pos.x, start_x - 1);
+    }
+
+    #[test]
+    fn rotating_then_hard_dropping_does_not_rotate_the_next_piece() {
+        let mut state = GameState::new(&test_settings());
+        state.tick(&press(&[Action::RotateClockwise]));
+
+        // Without key releases the rotate key may still count as held from the tap
+        let mut input = press(&[Action::HardDrop]);
+        input.held.insert(Action::RotateClockwise);
+        state.tick(&input);
+
+        assert_eq!(state.block_manager.block.rotation, 0);
+    }
+
+    #[test]
+    fn rotate_key_held_through_a_hard_drop_rotates_the_next_piece() {
+        let mut state = GameState::new(&test_settings());
+        state.tick(&press(&[Action::RotateClockwise]));
+
+        let mut input = press(&[Action::HardDrop]);
+        input.held.insert(Action::RotateClockwise);
+        input.reports_releases = true;
+        state.tick(&input);
+
+        assert_eq!(state.block_manager.block.rotation, 1);
+    }
+
+    #[test]
+    fn garbage_top_out() {
+        let mut state = GameState::new(&test_settings());
+        state.queue_garbage(40, 0);
+
+        let events = state.tick(&press(&[Action::HardDrop]));
+
+        assert!(events.contains(&GameEvent::GameOver(GameOverReason::TopOut(
+            TopOutReason::GarbageOut
+        ))));
+        assert!(state.game_over.is_some());
+    }
+}
@@ -0,0 +1,40 @@
+/// One row per frame. Gravity is measured in 1/65536ths of a row per frame, so fractions of a G can be represented exactly
+pub const G: u32 = 65536;
+/// Pieces can't fall any faster than this, and at this speed they appear already resting on the stack
+pub const MAX_GRAVITY: u32 = 20 * G;
+
+/// NES frames per row for levels 0 to 29 and beyond
+const NES_FRAMES_PER_ROW: [u32; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    1,
+];
+
+/// How quickly pieces fall at each level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GravityCurve {
+    /// The guideline formula, starting at one row per second on level 1 and reaching 20G on level 20
+    Guideline,
+    /// The speeds from NES Tetris, starting at level 0
+    Nes,
+    /// Custom gravity for each level, in the same units as [`G`]. Levels past the end of the table use the last entry
+    Table(Vec<u32>),
+}
+
+impl GravityCurve {
+    pub fn gravity(&self, level: usize) -> u32 {
+        match self {
+            Self::Guideline => {
+                let level = level.clamp(1, 20) as i32;
+                let seconds_per_row = 0.007f64.mul_add(-f64::from(level - 1), 0.8).powi(level - 1);
+                ((f64::from(G) / (seconds_per_row * 60.0)) as u32).min(MAX_GRAVITY)
+            }
+            Self::Nes => G.div_ceil(NES_FRAMES_PER_ROW[level.min(NES_FRAMES_PER_ROW.len() - 1)]),
+            Self::Table(table) => table
+                .get(level)
+                .or_else(|| table.last())
+                .copied()
+                .unwrap_or(0)
+                .min(MAX_GRAVITY),
+        }
+    }
+}
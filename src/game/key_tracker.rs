@@ -0,0 +1,272 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// How long to wait for a freshly pressed key to start repeating, for terminals that never report key releases. Operating systems usually wait up to 500ms before they start repeating a held key, so this must be longer
+const INITIAL_RELEASE_TIMEOUT: Duration = Duration::from_millis(600);
+/// How long a key counts as held after its last repeat, once it has started repeating. Repeats arrive far more often than the initial delay
+const REPEAT_RELEASE_TIMEOUT: Duration = Duration::from_millis(100);
+/// The shortest delay operating systems commonly wait before repeating a held key. Assumed until a key has been seen repeating
+const DEFAULT_REPEAT_DELAY: Duration = Duration::from_millis(250);
+/// Events are only read once a frame, so the time between two of them can be off by a frame or so
+const TIMING_TOLERANCE: Duration = Duration::from_millis(50);
+
+struct HeldKey {
+    last_seen: Instant,
+    /// Whether any repeat events have arrived since the key was pressed
+    is_repeating: bool,
+    /// How long after being pressed the key's first repeat arrived, until a second repeat confirms it
+    first_repeat_delay: Option<Duration>,
+}
+
+impl HeldKey {
+    const fn pressed(now: Instant) -> Self {
+        Self {
+            last_seen: now,
+            is_repeating: false,
+            first_repeat_delay: None,
+        }
+    }
+
+    const fn release_timeout(&self) -> Duration {
+        if self.is_repeating {
+            REPEAT_RELEASE_TIMEOUT
+        } else {
+            INITIAL_RELEASE_TIMEOUT
+        }
+    }
+}
+
+/// Keeps track of which keys are currently held down
+///
+/// Terminals using the kitty keyboard protocol report key releases, so keys are held exactly until they are released. Other terminals only send repeated presses while a key is held, so there a key only counts as held once it starts repeating, and is let go once the repeats stop. Until then a press is just a tap
+///
+/// Without releases, a second press is told apart from a repeat by timing. The OS waits a while before it starts repeating a key, so a press sooner than that is another tap
+pub struct KeyTracker {
+    held: HashMap<KeyCode, HeldKey>,
+    reports_releases: bool,
+    /// How long the OS waits before repeating a held key, as last measured
+    repeat_delay: Duration,
+}
+
+impl KeyTracker {
+    pub fn new(reports_releases: bool) -> Self {
+        Self {
+            held: HashMap::new(),
+            reports_releases,
+            repeat_delay: DEFAULT_REPEAT_DELAY,
+        }
+    }
+
+    /// Record a key event, returning true if it was a fresh press rather than a repeat or release
+    pub fn handle(&mut self, event: &KeyEvent) -> bool {
+        self.handle_at(event, Instant::now())
+    }
+
+    pub(super) fn handle_at(&mut self, event: &KeyEvent, now: Instant) -> bool {
+        match event.kind {
+            // Terminals that don't report releases send repeats as more presses
+            KeyEventKind::Press | KeyEventKind::Repeat => {
+                let is_repeat = event.kind == KeyEventKind::Repeat
+                    || self
+                        .held
+                        .get(&event.code)
+                        .is_some_and(|key| self.reports_releases || self.is_os_repeat(key, now));
+
+                if is_repeat {
+                    self.record_repeat(event.code, now);
+                } else {
+                    self.held.insert(event.code, HeldKey::pressed(now));
+                }
+                !is_repeat
+            }
+            KeyEventKind::Release => {
+                self.held.remove(&event.code);
+                false
+            }
+        }
+    }
+
+    /// Whether another press of a held key is the OS repeating it, rather than the key being tapped again
+    fn is_os_repeat(&self, key: &HeldKey, now: Instant) -> bool {
+        let gap = now.duration_since(key.last_seen);
+        // Nobody taps a key this quickly, but repeats come this often
+        if gap < REPEAT_RELEASE_TIMEOUT {
+            return true;
+        }
+
+        !key.is_repeating
+            && gap < INITIAL_RELEASE_TIMEOUT
+            && gap + TIMING_TOLERANCE >= self.repeat_delay
+    }
+
+    fn record_repeat(&mut self, code: KeyCode, now: Instant) {
+        let key = self
+            .held
+            .entry(code)
+            .or_insert_with(|| HeldKey::pressed(now));
+        if key.is_repeating {
+            // Repeats only come in quick succession, so a second one confirms the OS started repeating the key at the first
+            if let Some(delay) = key.first_repeat_delay.take() {
+                self.repeat_delay = delay;
+            }
+        } else {
+            key.is_repeating = true;
+            // A repeat straight after the press can't tell us how long the OS waits
+            let delay = now.duration_since(key.last_seen);
+            key.first_repeat_delay = (delay >= REPEAT_RELEASE_TIMEOUT).then_some(delay);
+        }
+        key.last_seen = now;
+    }
+
+    /// Let go of any keys which haven't been pressed or repeated recently, if the terminal doesn't report key releases
+    pub fn release_stale_keys(&mut self) {
+        self.release_stale_keys_at(Instant::now());
+    }
+
+    pub(super) fn release_stale_keys_at(&mut self, now: Instant) {
+        if !self.reports_releases {
+            self.held
+                .retain(|_, key| now.duration_since(key.last_seen) < key.release_timeout());
+        }
+    }
+
+    /// Whether keys are held until they are released, rather than until they stop repeating
+    pub const fn reports_releases(&self) -> bool {
+        self.reports_releases
+    }
+
+    /// Let go of every key
+    pub fn clear(&mut self) {
+        self.held.clear();
+    }
+
+    /// Returns true if the event is a `Ctrl+C` press
+    pub fn is_keyboard_interrupt(event: &KeyEvent) -> bool {
+        event.code == KeyCode::Char('c')
+            && event.modifiers.contains(KeyModifiers::CONTROL)
+            && event.kind == KeyEventKind::Press
+    }
+
+    /// The keys being held down. Without key releases, a key that hasn't started repeating may have been tapped, so it isn't included
+    pub fn held_keys(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.held
+            .iter()
+            .filter(|(_, key)| self.reports_releases || key.is_repeating)
+            .map(|(&code, _)| code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(code: KeyCode, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent::new_with_kind(code, KeyModifiers::NONE, kind)
+    }
+
+    fn held(keys: &KeyTracker) -> Vec<KeyCode> {
+        keys.held_keys().collect()
+    }
+
+    #[test]
+    fn key_is_held_once_the_os_starts_repeating() {
+        let start = Instant::now();
+        let mut keys = KeyTracker::new(false);
+        let press = event(KeyCode::Left, KeyEventKind::Press);
+
+        // Until it repeats, the key may only have been tapped
+        assert!(keys.handle_at(&press, start));
+        keys.release_stale_keys_at(start + Duration::from_millis(500));
+        assert_eq!(held(&keys), vec![]);
+
+        // The first repeat arrives as another press, and is not a fresh one
+        assert!(!keys.handle_at(&press, start + Duration::from_millis(500)));
+        keys.release_stale_keys_at(start + Duration::from_millis(530));
+        assert_eq!(held(&keys), vec![KeyCode::Left]);
+    }
+
+    #[test]
+    fn repeating_key_is_released_soon_after_repeats_stop() {
+        let start = Instant::now();
+        let mut keys = KeyTracker::new(false);
+        let press = event(KeyCode::Left, KeyEventKind::Press);
+
+        keys.handle_at(&press, start);
+        keys.handle_at(&press, start + Duration::from_millis(500));
+        keys.release_stale_keys_at(start + Duration::from_millis(650));
+        assert_eq!(held(&keys), vec![]);
+
+        // Pressing it again afterwards is a fresh press, which waits for repeats with the long timeout again
+        assert!(keys.handle_at(&press, start + Duration::from_millis(700)));
+        keys.release_stale_keys_at(start + Duration::from_millis(1100));
+        assert!(!keys.handle_at(&press, start + Duration::from_millis(1100)));
+        assert_eq!(held(&keys), vec![KeyCode::Left]);
+    }
+
+    #[test]
+    fn tapped_key_is_released_after_the_initial_timeout() {
+        let start = Instant::now();
+        let mut keys = KeyTracker::new(false);
+
+        let press = event(KeyCode::Left, KeyEventKind::Press);
+
+        keys.handle_at(&press, start);
+        keys.release_stale_keys_at(start + INITIAL_RELEASE_TIMEOUT);
+        assert!(keys.handle_at(&press, start + INITIAL_RELEASE_TIMEOUT));
+    }
+
+    #[test]
+    fn quick_double_tap_is_two_presses() {
+        let start = Instant::now();
+        let mut keys = KeyTracker::new(false);
+        let press = event(KeyCode::Left, KeyEventKind::Press);
+
+        assert!(keys.handle_at(&press, start));
+        assert!(keys.handle_at(&press, start + Duration::from_millis(150)));
+        assert_eq!(held(&keys), vec![]);
+    }
+
+    #[test]
+    fn double_tap_is_two_presses_once_the_repeat_delay_is_known() {
+        let start = Instant::now();
+        let mut keys = KeyTracker::new(false);
+        let press = event(KeyCode::Left, KeyEventKind::Press);
+
+        // Hold the key long enough to see that the OS waits 500ms before repeating it
+        for millis in [0, 500, 530, 560] {
+            keys.handle_at(&press, start + Duration::from_millis(millis));
+        }
+        keys.release_stale_keys_at(start + Duration::from_millis(700));
+
+        let start = start + Duration::from_secs(1);
+        assert!(keys.handle_at(&press, start));
+        assert!(keys.handle_at(&press, start + Duration::from_millis(350)));
+        // Repeats are still repeats
+        assert!(!keys.handle_at(&press, start + Duration::from_millis(850)));
+        assert!(!keys.handle_at(&press, start + Duration::from_millis(880)));
+    }
+
+    #[test]
+    fn keys_are_held_until_released_when_releases_are_reported() {
+        let start = Instant::now();
+        let mut keys = KeyTracker::new(true);
+
+        assert!(keys.handle_at(&event(KeyCode::Left, KeyEventKind::Press), start));
+        assert!(!keys.handle_at(
+            &event(KeyCode::Left, KeyEventKind::Repeat),
+            start + Duration::from_millis(500)
+        ));
+        keys.release_stale_keys_at(start + Duration::from_secs(10));
+        assert_eq!(held(&keys), vec![KeyCode::Left]);
+
+        assert!(!keys.handle_at(
+            &event(KeyCode::Left, KeyEventKind::Release),
+            start + Duration::from_secs(10)
+        ));
+        assert_eq!(held(&keys), vec![]);
+    }
+}
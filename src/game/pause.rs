@@ -1,6 +1,7 @@
-use console_input::keypress::exit_raw_mode;
 use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
+use super::terminal;
+
 pub fn pause() {
     println!("-- Paused (Esc to unpause) --\r");
     loop {
@@ -17,7 +18,7 @@ pub fn pause() {
                     modifiers: KeyModifiers::CONTROL,
                     kind: KeyEventKind::Press,
                     ..
-                } => exit_raw_mode(),
+                } => terminal::exit(),
                 _ => (),
             }
         }
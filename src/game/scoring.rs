@@ -0,0 +1,240 @@
+use super::{
+    alerts::{
+        generate_alert_for_filled_lines, generate_alert_for_perfect_clear, generate_alert_for_spin,
+        priorised_alert,
+    },
+    block_manager::Spin,
+};
+
+/// Points for each lock in a combo, multiplied by how long the combo is
+const COMBO_SCORE: isize = 50;
+/// Points for clearing 1 to 4 lines in NES Tetris, multiplied by the level plus one
+const NES_LINE_SCORES: [isize; 4] = [40, 100, 300, 1200];
+
+/// Everything about a locked piece that a scoring system may reward
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lock {
+    pub cleared_lines: isize,
+    pub spin: Option<Spin>,
+    pub is_perfect_clear: bool,
+    /// Whether this clear continues a chain of difficult clears
+    pub is_back_to_back: bool,
+    /// How many locks in a row have cleared lines, minus one
+    pub combo: isize,
+    /// The level the piece locked on, before any level up from its clear
+    pub level: usize,
+}
+
+/// Decides how many points everything the player does is worth
+pub trait ScoringSystem {
+    /// Points for moving the active piece down `rows` rows with soft or hard drop
+    fn score_drop(&mut self, rows: isize, is_hard_drop: bool) -> isize;
+    /// Points for locking a piece, as `(score, alert text)` pairs
+    fn score_lock(&mut self, lock: &Lock) -> Vec<(isize, String)>;
+}
+
+/// The scoring systems to choose from when starting a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoringSystemType {
+    /// Line clears, spins, combos and perfect clears multiplied by the level, with a bonus for back-to-backs
+    Guideline,
+    /// Only line clears score, multiplied by the level plus one, as in NES Tetris
+    Nes,
+    /// Line clears score more the higher the level, the longer the combo and the further the piece was dropped, as in Tetris The Grand Master
+    Tgm,
+}
+
+impl ScoringSystemType {
+    pub fn build(self) -> Box<dyn ScoringSystem> {
+        match self {
+            Self::Guideline => Box::new(Guideline),
+            Self::Nes => Box::new(Nes),
+            Self::Tgm => Box::new(Tgm {
+                combo: 1,
+                dropped_rows: 0,
+            }),
+        }
+    }
+}
+
+/// The name of a line clear, without its score
+fn line_clear_text(cleared_lines: isize) -> Option<String> {
+    generate_alert_for_filled_lines(cleared_lines).map(|(_, text)| text)
+}
+
+struct Guideline;
+
+impl ScoringSystem for Guideline {
+    fn score_drop(&mut self, rows: isize, is_hard_drop: bool) -> isize {
+        if is_hard_drop {
+            rows * 2
+        } else {
+            rows
+        }
+    }
+
+    fn score_lock(&mut self, lock: &Lock) -> Vec<(isize, String)> {
+        let multiplier = lock.level.max(1) as isize;
+        let mut alerts = vec![];
+
+        // Score the most significant clear
+        if let Some((score, mut text)) = priorised_alert(&[
+            lock.spin
+                .map(|spin| generate_alert_for_spin(spin, lock.cleared_lines)),
+            generate_alert_for_filled_lines(lock.cleared_lines),
+        ]) {
+            let mut score = score * multiplier;
+            if lock.is_back_to_back {
+                score = score * 3 / 2;
+                text = format!("Back-to-Back {text}");
+            }
+            alerts.push((score, text));
+        }
+
+        if lock.is_perfect_clear {
+            let (score, text) =
+                generate_alert_for_perfect_clear(lock.cleared_lines, lock.is_back_to_back);
+            alerts.push((score * multiplier, text));
+        }
+
+        if lock.combo > 0 {
+            alerts.push((
+                COMBO_SCORE * lock.combo * multiplier,
+                format!("{} Combo", lock.combo),
+            ));
+        }
+
+        alerts
+    }
+}
+
+struct Nes;
+
+impl ScoringSystem for Nes {
+    /// NES Tetris has no hard drop, so only soft drops score
+    fn score_drop(&mut self, rows: isize, is_hard_drop: bool) -> isize {
+        if is_hard_drop {
+            0
+        } else {
+            rows
+        }
+    }
+
+    fn score_lock(&mut self, lock: &Lock) -> Vec<(isize, String)> {
+        let base = match lock.cleared_lines {
+            lines @ 1..=4 => NES_LINE_SCORES[lines as usize - 1],
+            lines => NES_LINE_SCORES[3] * lines / 4,
+        };
+
+        line_clear_text(lock.cleared_lines)
+            .map(|text| (base * (lock.level as isize + 1), text))
+            .into_iter()
+            .collect()
+    }
+}
+
+struct Tgm {
+    /// Grows by `2 * lines - 2` for each clear in a row, and resets to 1 when a lock clears nothing
+    combo: isize,
+    /// How many rows the active piece has been dropped by the player
+    dropped_rows: isize,
+}
+
+impl ScoringSystem for Tgm {
+    /// Drops score nothing straight away, but are added to the piece's line clear bonus
+    fn score_drop(&mut self, rows: isize, _is_hard_drop: bool) -> isize {
+        self.dropped_rows += rows;
+        0
+    }
+
+    fn score_lock(&mut self, lock: &Lock) -> Vec<(isize, String)> {
+        let dropped_rows = std::mem::take(&mut self.dropped_rows);
+        let Some(mut text) = line_clear_text(lock.cleared_lines) else {
+            self.combo = 1;
+            return vec![];
+        };
+        self.combo += 2 * lock.cleared_lines - 2;
+
+        let level_bonus = (lock.level as isize + lock.cleared_lines + 3) / 4;
+        let mut score = (level_bonus + dropped_rows) * lock.cleared_lines * self.combo;
+        if lock.is_perfect_clear {
+            score *= 4;
+            text = format!("Bravo! {text}");
+        }
+
+        vec![(score, text)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(cleared_lines: isize, level: usize) -> Lock {
+        Lock {
+            cleared_lines,
+            spin: None,
+            is_perfect_clear: false,
+            is_back_to_back: false,
+            combo: 0,
+            level,
+        }
+    }
+
+    #[test]
+    fn guideline_multiplies_by_level() {
+        let mut scoring = ScoringSystemType::Guideline.build();
+
+        let alerts = scoring.score_lock(&Lock {
+            is_back_to_back: true,
+            combo: 2,
+            ..clear(4, 3)
+        });
+
+        assert_eq!(
+            alerts,
+            vec![
+                (3600, String::from("Back-to-Back Tetris!")),
+                (300, String::from("2 Combo")),
+            ]
+        );
+    }
+
+    #[test]
+    fn nes_tetris() {
+        let mut scoring = ScoringSystemType::Nes.build();
+
+        assert_eq!(
+            scoring.score_lock(&clear(4, 0)),
+            vec![(1200, String::from("Tetris!"))]
+        );
+        assert_eq!(
+            scoring.score_lock(&clear(1, 9)),
+            vec![(400, String::from("Single!"))]
+        );
+        assert_eq!(scoring.score_lock(&clear(0, 9)), vec![]);
+    }
+
+    #[test]
+    fn tgm_combo_and_drop_bonus() {
+        let mut scoring = ScoringSystemType::Tgm.build();
+
+        // (ceil((10 + 2) / 4) + 5) * 2 * 3
+        assert_eq!(scoring.score_drop(5, true), 0);
+        assert_eq!(
+            scoring.score_lock(&clear(2, 10)),
+            vec![(48, String::from("Double!"))]
+        );
+        // The combo grows to 3 + 2 * 1 - 2 and the drop bonus is used up
+        assert_eq!(
+            scoring.score_lock(&clear(1, 10)),
+            vec![(9, String::from("Single!"))]
+        );
+        // Locking without clearing resets the combo
+        assert_eq!(scoring.score_lock(&clear(0, 10)), vec![]);
+        assert_eq!(
+            scoring.score_lock(&clear(1, 10)),
+            vec![(3, String::from("Single!"))]
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use super::{
+    block_manager::{RandomizerType, RotationSystemType},
+    game_mode::GameMode,
+    gravity::GravityCurve,
+    scoring::ScoringSystemType,
+};
+
+/// When the lock delay timer restarts while a piece is resting on the stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockReset {
+    /// Moving or rotating the piece restarts the timer, up to `max_resets` times per piece. Reaching a new lowest row restores the resets. The guideline uses 15
+    OnMove { max_resets: u32 },
+    /// Only falling to a new lowest row restarts the timer
+    OnStep,
+    /// The timer never restarts, so every piece gets exactly one lock delay (classic)
+    Never,
+}
+
+impl LockReset {
+    /// Up to 15 move resets, as in the guideline
+    pub const GUIDELINE: Self = Self::OnMove { max_resets: 15 };
+}
+
+/// How fast the active piece falls while the soft drop key is held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoftDrop {
+    /// Fall this many times faster than the current gravity. The guideline uses 20
+    Factor(u32),
+    /// Fall straight to the stack without locking ("sonic" soft drop)
+    Instant,
+}
+
+/// Everything that can be tweaked about a game before it starts
+#[derive(Debug, Clone)]
+pub struct GameSettings {
+    pub mode: GameMode,
+    /// Width of the playfield in cells. Must be between 4 and 64
+    pub board_width: usize,
+    /// Height of the visible playfield in cells. Must be between 4 and 64
+    pub board_height: usize,
+    /// Number of rows in the vanish zone above the visible playfield, where pieces spawn
+    pub hidden_rows: usize,
+    /// Lock delay, in frames
+    pub block_place_cooldown: u32,
+    pub lock_reset: LockReset,
+    pub start_level: usize,
+    /// How many lines must be cleared to move up a level
+    pub lines_per_level: usize,
+    pub gravity_curve: GravityCurve,
+    /// Delayed auto shift: how many frames a shift key must be held before the block starts moving repeatedly
+    pub das: u32,
+    /// Auto-repeat rate: frames between each repeated move once DAS has charged. 0 moves the block straight to the wall
+    pub arr: u32,
+    /// How many frames auto-repeat pauses for after a rotation
+    pub das_cut_delay: u32,
+    pub soft_drop: SoftDrop,
+    pub piece_preview_count: usize,
+    pub randomizer: RandomizerType,
+    /// The seed for every random choice in the game, such as the order of pieces. `None` picks one at random
+    pub seed: Option<u64>,
+    pub rotation_system: RotationSystemType,
+    /// Whether the 180 rotation key does anything. Uses the SRS+ kick table
+    pub allow_180_rotation: bool,
+    /// Whether every piece can spin, not just T. Any piece that rotates into a spot it can't move left, right or up out of counts as a spin
+    pub all_spin: bool,
+    pub scoring: ScoringSystemType,
+    /// Whether holding a rotate or hold key as a piece spawns rotates or holds it straight away (IRS and IHS). On terminals that don't report key releases, only keys pressed while waiting for the piece count
+    pub initial_actions: bool,
+    /// Entry delay: frames between a piece locking and the next one entering. TGM uses 30
+    pub are: u32,
+    /// Frames that cleared lines stay on the board before disappearing, before ARE starts. TGM uses 41
+    pub line_clear_delay: u32,
+}
@@ -0,0 +1,63 @@
+use std::{
+    io::stdout,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use console_input::keypress::exit_raw_mode;
+use crossterm::{
+    event::{
+        poll, read, Event, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
+    execute, terminal,
+};
+
+static KEY_RELEASE_EVENTS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Ask the terminal to report key repeats and releases using the kitty keyboard protocol, if it supports it. Should be called after raw mode is enabled
+///
+/// Returns true if key releases will be reported
+pub fn enable_key_release_events() -> bool {
+    let enabled = terminal::supports_keyboard_enhancement().unwrap_or(false)
+        && execute!(
+            stdout(),
+            PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+            )
+        )
+        .is_ok();
+
+    KEY_RELEASE_EVENTS_ENABLED.store(enabled, Ordering::Relaxed);
+    enabled
+}
+
+/// Undo any changes made to the terminal, disable raw mode and exit the process
+pub fn exit() {
+    if KEY_RELEASE_EVENTS_ENABLED.load(Ordering::Relaxed) {
+        // We're exiting anyway, so there's nothing to be done if this fails
+        let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+    }
+
+    exit_raw_mode();
+}
+
+/// Sleep for the rest of the frame, given how long the frame has taken so far, and collect every event that arrives in the meantime, in order. Any events still queued once the frame is over are collected too, so none are ever dropped
+///
+/// The returned bool is true if the frame took longer than `1/fps`, in which case rendering the next frame should be skipped
+pub fn sleep_and_read_events(fps: f32, elapsed: Duration) -> (bool, Vec<Event>) {
+    let frame_duration = Duration::from_secs_f32(1.0 / fps);
+    let frame_skip = elapsed >= frame_duration;
+    let frame_end = Instant::now() + frame_duration.saturating_sub(elapsed);
+
+    let mut events = vec![];
+    while poll(frame_end.saturating_duration_since(Instant::now())).unwrap_or(false) {
+        match read() {
+            Ok(event) => events.push(event),
+            Err(_) => break,
+        }
+    }
+
+    (frame_skip, events)
+}
@@ -1,26 +1,73 @@
 use console_input::keypress::enable_raw_mode;
 use gemini_engine::gameloop::MainLoopRoot;
+mod cli;
 mod game;
-use game::Game;
+use game::{
+    Game, GameMode, GameSettings, GravityCurve, LockReset, RandomizerType, RotationSystemType,
+    ScoringSystemType, SoftDrop, FPS,
+};
 
-const FPS: f32 = 60.0;
+const GAME_MODE: GameMode = GameMode::Endless;
+const BOARD_WIDTH: usize = 10;
+const BOARD_HEIGHT: usize = 20;
+const BOARD_HIDDEN_ROWS: usize = 20;
 const BLOCK_PLACE_COOLDOWN: u32 = 30;
+const LOCK_RESET: LockReset = LockReset::GUIDELINE;
+const START_LEVEL: usize = 1;
+const LINES_PER_LEVEL: usize = 10;
+const DAS: u32 = 10;
+const ARR: u32 = 2;
+const DAS_CUT_DELAY: u32 = 0;
+const SOFT_DROP: SoftDrop = SoftDrop::Factor(20);
 const PIECE_PREVIEW_COUNT: usize = 3;
+const RANDOMIZER: RandomizerType = RandomizerType::SevenBag;
+const SEED: Option<u64> = None;
+const ROTATION_SYSTEM: RotationSystemType = RotationSystemType::Srs;
+const ALLOW_180_ROTATION: bool = true;
+const ALL_SPIN: bool = false;
+const SCORING: ScoringSystemType = ScoringSystemType::Guideline;
+const INITIAL_ACTIONS: bool = true;
+const ARE: u32 = 0;
+const LINE_CLEAR_DELAY: u32 = 0;
 const CONTROLS_HELP_TEXT: &str = "Controls:
 C to hold
 Left/Right to shift
 Space hard | Down soft
 Z AC | Up/X C rotation
+A to rotate 180
 Esc to pause";
 
 fn main() {
+    let mut settings = GameSettings {
+        mode: GAME_MODE,
+        board_width: BOARD_WIDTH,
+        board_height: BOARD_HEIGHT,
+        hidden_rows: BOARD_HIDDEN_ROWS,
+        block_place_cooldown: BLOCK_PLACE_COOLDOWN,
+        lock_reset: LOCK_RESET,
+        start_level: START_LEVEL,
+        lines_per_level: LINES_PER_LEVEL,
+        gravity_curve: GravityCurve::Guideline,
+        das: DAS,
+        arr: ARR,
+        das_cut_delay: DAS_CUT_DELAY,
+        soft_drop: SOFT_DROP,
+        piece_preview_count: PIECE_PREVIEW_COUNT,
+        randomizer: RANDOMIZER,
+        seed: SEED,
+        rotation_system: ROTATION_SYSTEM,
+        allow_180_rotation: ALLOW_180_ROTATION,
+        all_spin: ALL_SPIN,
+        scoring: SCORING,
+        initial_actions: INITIAL_ACTIONS,
+        are: ARE,
+        line_clear_delay: LINE_CLEAR_DELAY,
+    };
+    cli::apply_args(&mut settings);
+
     enable_raw_mode();
 
-    let mut game = Game::new(
-        BLOCK_PLACE_COOLDOWN,
-        PIECE_PREVIEW_COUNT,
-        CONTROLS_HELP_TEXT,
-    );
+    let mut game = Game::new(&settings, CONTROLS_HELP_TEXT);
 
     game.main_loop(FPS);
 }